@@ -0,0 +1,109 @@
+//! Baseline numbers for the parser: end-to-end [`MIDI::try_from`] on files of
+//! varying size, and the variable-length quantity reader it relies on.
+
+use std::hint::black_box;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use relocate_midi::{
+    core::chunk::{header::format::Format, track::builder::TrackBuilder},
+    prelude::*,
+};
+
+/// The number of notes of the synthetic file, per track.
+const SYNTHETIC_NOTES: u64 = 50_000;
+
+/// The number of tracks of the synthetic file.
+const SYNTHETIC_TRACKS: u16 = 16;
+
+/// A large Format 1 file of [`SYNTHETIC_TRACKS`] tracks, one per channel,
+/// each playing [`SYNTHETIC_NOTES`] overlapping notes with running status.
+fn synthetic_file() -> Vec<u8> {
+    let header = HeaderChunk::new(
+        Format::SimultaneousTracks,
+        SYNTHETIC_TRACKS,
+        Division::TicksPerQuarterNote(480),
+    )
+    .expect("a Format 1 file may have any number of tracks");
+
+    let mut bytes = Vec::try_from(&header).expect("the division fits in 15 bits");
+    for index in 0..SYNTHETIC_TRACKS {
+        let channel = Channel::new((index % 16) as u8).expect("index is in 0..16");
+        let mut builder = TrackBuilder::new();
+        for note in 0..SYNTHETIC_NOTES {
+            let key = 36 + (note % 48) as u8;
+            let note_event = |velocity| {
+                Event::Channel(ChannelEvent {
+                    channel,
+                    message: ChannelMessage::NoteOn { key, velocity },
+                })
+            };
+            builder
+                .push_at(note * 120, note_event(100))
+                .push_at(note * 120 + 240, note_event(0));
+        }
+        bytes.extend(Vec::try_from(&builder.build().unwrap()).expect("the events fit in a file"));
+    }
+    bytes
+}
+
+fn parse(criterion: &mut Criterion) {
+    let files = [
+        (
+            "Lapis Lazuli",
+            include_bytes!("../../../assets/Lapis Lazuli.mid").to_vec(),
+        ),
+        (
+            "World Vanquisher",
+            include_bytes!("../../../assets/World Vanquisher.mid").to_vec(),
+        ),
+        ("synthetic", synthetic_file()),
+    ];
+
+    let mut group = criterion.benchmark_group("MIDI::try_from");
+    for (name, bytes) in &files {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            bytes,
+            |bencher, bytes| {
+                bencher.iter_batched(
+                    || bytes.clone(),
+                    |bytes| MIDI::try_from(bytes).expect("the file is valid"),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn variable_length_quantity(criterion: &mut Criterion) {
+    // Every length from 1 to 4 bytes, as found in delta-times.
+    let quantities: [&[u8]; 4] = [
+        &[0x40],
+        &[0x81, 0x00],
+        &[0xC0, 0x80, 0x00],
+        &[0xFF, 0xFF, 0xFF, 0x7F],
+    ];
+    let bytes: Vec<u8> = quantities
+        .iter()
+        .cycle()
+        .take(4096)
+        .flat_map(|quantity| quantity.iter().copied())
+        .collect();
+
+    let mut group = criterion.benchmark_group("Scanner::eat_variable_length_quantity");
+    group.throughput(Throughput::Elements(4096));
+    group.bench_function("mixed lengths", |bencher| {
+        bencher.iter(|| {
+            let mut scanner = Scanner::new(black_box(&bytes));
+            while let Some(quantity) = scanner.eat_variable_length_quantity() {
+                black_box(quantity);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, variable_length_quantity);
+criterion_main!(benches);
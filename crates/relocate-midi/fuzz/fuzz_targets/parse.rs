@@ -0,0 +1,25 @@
+//! Parsing arbitrary bytes must return `Err` rather than panic, with or
+//! without recovery, and whatever parses must serialize back into bytes that
+//! parse again.
+//!
+//! Run with `cargo +nightly fuzz run parse` from `crates/relocate-midi`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use relocate_midi::{core::midi::MIDI, file::midi::MIDIFile, options::ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let midi_file = MIDIFile::from(data.to_vec());
+
+    let lenient = ParseOptions {
+        recover_chunk_lengths: true,
+        synthesize_end_of_track: true,
+        ..ParseOptions::default()
+    };
+    for options in [ParseOptions::default(), lenient] {
+        if let Ok(midi) = MIDI::try_from_with_options(&midi_file, &options) {
+            assert!(MIDI::try_from(Vec::try_from(&midi).unwrap()).is_ok());
+        }
+    }
+});
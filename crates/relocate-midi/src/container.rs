@@ -0,0 +1,146 @@
+//! A module recognizing the containers a Standard MIDI File may come in,
+//! without trusting the file extension.
+//!
+//! Besides bare Standard MIDI Files, some Windows and game assets wrap one in
+//! a RIFF `RMID` container, as the data of its `data` subchunk.
+
+use crate::{
+    file::chunk::header::{HEADER_CHUNK_KIND, HEADER_CHUNK_LENGTH},
+    scanner::Scanner,
+};
+
+/// The kind of a RIFF file, which is followed by its length and form type.
+const RIFF_KIND: &[u8; 4] = b"RIFF";
+
+/// The form type of a RIFF file wrapping a Standard MIDI File.
+const RMID_FORM_TYPE: &[u8; 4] = b"RMID";
+
+/// The kind of the RIFF subchunk containing the Standard MIDI File.
+const RMID_DATA_KIND: &[u8; 4] = b"data";
+
+/// Whether `bytes` start like a bare Standard MIDI File: with `MThd` followed
+/// by a header length of at least 6 bytes.
+///
+/// Only the first 8 bytes are read, so this is cheap enough to filter a whole
+/// directory.
+pub fn is_smf(bytes: &[u8]) -> bool {
+    let mut scanner = Scanner::new(bytes);
+    scanner.eat_bytes::<4>() == Some(HEADER_CHUNK_KIND)
+        && scanner
+            .eat_u32_be()
+            .is_some_and(|length| length >= *HEADER_CHUNK_LENGTH)
+}
+
+/// The offset of the Standard MIDI File in `bytes`: `0` for a bare one, or the
+/// offset of the embedded `MThd` for one wrapped in a RIFF `RMID` container.
+///
+/// Returns `None` if `bytes` contain neither.
+pub fn smf_offset(bytes: &[u8]) -> Option<usize> {
+    if is_smf(bytes) {
+        return Some(0);
+    }
+    let (offset, data) = rmid_data(bytes)?;
+    is_smf(data).then_some(offset)
+}
+
+/// The Standard MIDI File wrapped in `bytes`, if they are a RIFF `RMID`
+/// container: the data of its `data` subchunk, ready for
+/// [`ChunksFile::try_from_with_options`](crate::file::chunk::ChunksFile::try_from_with_options).
+///
+/// Returns `None` if `bytes` are not a RIFF `RMID` container, or its `data`
+/// subchunk is missing or truncated, so that callers can fall back to
+/// parsing `bytes` as a bare Standard MIDI File.
+pub fn unwrap_rmid(bytes: &[u8]) -> Option<&[u8]> {
+    rmid_data(bytes).map(|(_, data)| data)
+}
+
+/// The offset and data of the `data` subchunk of a RIFF `RMID` container, or
+/// `None` if `bytes` are not one or the subchunk is missing or truncated.
+fn rmid_data(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    let mut scanner = Scanner::new(bytes);
+    if scanner.eat_bytes::<4>()? != RIFF_KIND {
+        return None;
+    }
+    // Unlike a Standard MIDI File, RIFF lengths are little-endian.
+    scanner.eat_bytes::<4>()?;
+    if scanner.eat_bytes::<4>()? != RMID_FORM_TYPE {
+        return None;
+    }
+
+    while !scanner.done() {
+        let kind = scanner.eat_bytes::<4>()?;
+        let length = u32::from_le_bytes(*scanner.eat_bytes::<4>()?) as usize;
+        let offset = scanner.cursor();
+        let data = scanner.eat_slice(length)?;
+        if kind == RMID_DATA_KIND {
+            return Some((offset, data));
+        }
+        // Subchunks are padded to an even length.
+        if length % 2 == 1 {
+            scanner.eat();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::core::midi::MIDI;
+
+    const LAPIS_LAZULI: &[u8] = include_bytes!("../../../assets/Lapis Lazuli.mid");
+
+    /// `smf` wrapped in a RIFF `RMID` container, after an odd-length `LIST`
+    /// subchunk if `with_list` is set.
+    fn rmid(smf: &[u8], with_list: bool) -> Vec<u8> {
+        let mut body = RMID_FORM_TYPE.to_vec();
+        if with_list {
+            body.extend(b"LIST\x03\0\0\0abc\0");
+        }
+        body.extend(RMID_DATA_KIND);
+        body.extend((smf.len() as u32).to_le_bytes());
+        body.extend(smf);
+
+        let mut bytes = RIFF_KIND.to_vec();
+        bytes.extend((body.len() as u32).to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    #[test]
+    fn detects_bare_smf() {
+        assert!(is_smf(LAPIS_LAZULI));
+        assert_eq!(smf_offset(LAPIS_LAZULI), Some(0));
+        assert!(!is_smf(b"MThd\0\0\0\x05"));
+        assert!(!is_smf(b"MTh"));
+        assert_eq!(smf_offset(b"hello world"), None);
+    }
+
+    #[test]
+    fn detects_smf_wrapped_in_rmid() {
+        let bytes = rmid(LAPIS_LAZULI, false);
+        let bytes_with_list = rmid(LAPIS_LAZULI, true);
+
+        assert!(!is_smf(&bytes));
+        assert_eq!(smf_offset(&bytes), Some(20));
+        assert_eq!(smf_offset(&bytes_with_list), Some(32));
+        assert_eq!(smf_offset(&rmid(b"nope", false)), None);
+    }
+
+    #[test]
+    fn unwrap_rmid_returns_wrapped_smf() {
+        let bytes = rmid(LAPIS_LAZULI, true);
+
+        let smf = unwrap_rmid(&bytes).unwrap();
+
+        assert_eq!(smf, LAPIS_LAZULI);
+        assert_eq!(
+            MIDI::try_from(smf.to_vec()).unwrap(),
+            MIDI::try_from(LAPIS_LAZULI.to_vec()).unwrap()
+        );
+        assert_eq!(unwrap_rmid(LAPIS_LAZULI), None);
+        assert_eq!(unwrap_rmid(&bytes[..bytes.len() - 10]), None);
+    }
+}
@@ -1,6 +1,7 @@
 use derive_more::{Debug, Display, Eq, Error, PartialEq};
 
-#[derive(Debug, Display, PartialEq, Eq)]
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Fps {
     FPS24 = -24,
     FPS25 = -25,
@@ -8,6 +9,21 @@ pub enum Fps {
     FPS30 = -30,
 }
 
+impl Fps {
+    /// The actual number of frames per second.
+    ///
+    /// [`Fps::FPS30Drop`] is the NTSC drop-frame rate, which runs at
+    /// 30000/1001 (about 29.97) frames per second rather than 30.
+    pub fn frames_per_second(&self) -> f64 {
+        match self {
+            Fps::FPS24 => 24.0,
+            Fps::FPS25 => 25.0,
+            Fps::FPS30Drop => 30000.0 / 1001.0,
+            Fps::FPS30 => 30.0,
+        }
+    }
+}
+
 #[derive(Debug, Display, Error, PartialEq, Eq)]
 pub enum TryFromError {
     InvalidFPS,
@@ -26,3 +42,28 @@ impl TryFrom<u8> for Fps {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fps_24() {
+        assert_eq!(Fps::FPS24.frames_per_second(), 24.0);
+    }
+
+    #[test]
+    fn fps_25() {
+        assert_eq!(Fps::FPS25.frames_per_second(), 25.0);
+    }
+
+    #[test]
+    fn fps_30_drop() {
+        assert!((Fps::FPS30Drop.frames_per_second() - 29.97).abs() < 0.001);
+    }
+
+    #[test]
+    fn fps_30() {
+        assert_eq!(Fps::FPS30.frames_per_second(), 30.0);
+    }
+}
@@ -1,8 +1,11 @@
+use derive_more::{Debug, Display, Error};
+
 use crate::core::chunk::header::division::fps::{Fps, TryFromError};
 
 pub mod fps;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Division {
     /// For metrical time.
     TicksPerQuarterNote(u16),
@@ -14,6 +17,78 @@ pub enum Division {
     },
 }
 
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    /// The ticks per quarter-note of a [`Division::TicksPerQuarterNote`] do
+    /// not fit in the 15 bits available.
+    InvalidTicksPerQuarterNote,
+}
+
+impl Division {
+    /// Creates a metrical [`Division`] of `ticks` per quarter-note.
+    ///
+    /// Returns `None` if `ticks` does not fit in the 15 bits available.
+    pub fn ticks_per_quarter(ticks: u16) -> Option<Self> {
+        if ticks & 0x8000 != 0 {
+            return None;
+        }
+        Some(Division::TicksPerQuarterNote(ticks))
+    }
+
+    /// Creates a time-code-based [`Division`] of `ticks_per_frame` ticks in
+    /// each of the `frames_per_second` frames.
+    pub fn time_code(frames_per_second: Fps, ticks_per_frame: u8) -> Self {
+        Division::TimeCode {
+            frames_per_second,
+            ticks_per_frame,
+        }
+    }
+
+    /// The ticks per quarter-note of a metrical [`Division`], or `None` for
+    /// a time-code-based one.
+    #[inline]
+    pub fn as_ticks_per_quarter(&self) -> Option<u16> {
+        match self {
+            Division::TicksPerQuarterNote(ticks) => Some(*ticks),
+            Division::TimeCode { .. } => None,
+        }
+    }
+
+    /// The frames per second and ticks per frame of a time-code-based
+    /// [`Division`], or `None` for a metrical one.
+    ///
+    /// The frames per second are the actual rate of
+    /// [`Fps::frames_per_second`], about 29.97 rather than 30 for drop-frame.
+    #[inline]
+    pub fn as_time_code(&self) -> Option<(f64, u8)> {
+        match self {
+            Division::TicksPerQuarterNote(_) => None,
+            Division::TimeCode {
+                frames_per_second,
+                ticks_per_frame,
+            } => Some((frames_per_second.frames_per_second(), *ticks_per_frame)),
+        }
+    }
+
+    /// The number of seconds elapsed at the absolute `tick` of a track, for a
+    /// time-code-based [`Division`].
+    ///
+    /// Time-code-based delta-times are subdivisions of a second and do not
+    /// depend on tempo. Returns `None` for a metrical [`Division`], whose
+    /// ticks can only be converted to seconds through the tempo map.
+    pub fn smpte_seconds_at(&self, tick: u64) -> Option<f64> {
+        match self {
+            Division::TicksPerQuarterNote(_) => None,
+            Division::TimeCode {
+                frames_per_second,
+                ticks_per_frame,
+            } => Some(
+                tick as f64 / (f64::from(*ticks_per_frame) * frames_per_second.frames_per_second()),
+            ),
+        }
+    }
+}
+
 impl TryFrom<[u8; 2]> for Division {
     type Error = TryFromError;
 
@@ -38,3 +113,97 @@ impl TryFrom<[u8; 2]> for Division {
         })
     }
 }
+
+impl TryFrom<&Division> for [u8; 2] {
+    type Error = EncodeError;
+
+    /// Encodes `value` back into its 2-byte form, the inverse of
+    /// `Division::try_from([u8; 2])`.
+    ///
+    /// Bit 15 is cleared for [`Division::TicksPerQuarterNote`], so ticks that
+    /// need it are an error rather than being read back as a
+    /// [`Division::TimeCode`]. For [`Division::TimeCode`], the upper byte
+    /// holds the negative frames per second in two's complement form, which
+    /// sets bit 15.
+    fn try_from(value: &Division) -> Result<Self, Self::Error> {
+        match value {
+            Division::TicksPerQuarterNote(ticks) if ticks & 0x8000 != 0 => {
+                Err(EncodeError::InvalidTicksPerQuarterNote)
+            }
+            Division::TicksPerQuarterNote(ticks) => Ok(ticks.to_be_bytes()),
+            Division::TimeCode {
+                frames_per_second,
+                ticks_per_frame,
+            } => Ok([*frames_per_second as i8 as u8, *ticks_per_frame]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smpte_seconds_at_25_fps() {
+        assert_eq!(
+            Division::time_code(Fps::FPS25, 40).smpte_seconds_at(30000),
+            Some(30.0)
+        );
+    }
+
+    #[test]
+    fn smpte_seconds_at_30_drop_frame() {
+        let seconds = Division::time_code(Fps::FPS30Drop, 1)
+            .smpte_seconds_at(30000)
+            .unwrap();
+        assert!((seconds - 1001.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn smpte_seconds_at_metrical() {
+        assert_eq!(
+            Division::TicksPerQuarterNote(480).smpte_seconds_at(30000),
+            None
+        );
+    }
+
+    #[test]
+    fn division_round_trips() {
+        for division in [
+            Division::TicksPerQuarterNote(480),
+            Division::TicksPerQuarterNote(0x7FFF),
+            Division::time_code(Fps::FPS24, 4),
+            Division::time_code(Fps::FPS30Drop, 80),
+        ] {
+            let bytes = <[u8; 2]>::try_from(&division).unwrap();
+            assert_eq!(Division::try_from(bytes).unwrap(), division);
+        }
+    }
+
+    #[test]
+    fn time_code_sets_bit_15() {
+        assert_eq!(
+            <[u8; 2]>::try_from(&Division::time_code(Fps::FPS24, 4)).unwrap(),
+            [0xE8, 4]
+        );
+    }
+
+    #[test]
+    fn ticks_per_quarter_note_above_15_bits_is_rejected() {
+        assert!(matches!(
+            <[u8; 2]>::try_from(&Division::TicksPerQuarterNote(0x8000)),
+            Err(EncodeError::InvalidTicksPerQuarterNote)
+        ));
+    }
+
+    #[test]
+    fn as_ticks_per_quarter_and_as_time_code() {
+        let metrical = Division::TicksPerQuarterNote(480);
+        let time_code = Division::time_code(Fps::FPS25, 40);
+
+        assert_eq!(metrical.as_ticks_per_quarter(), Some(480));
+        assert_eq!(metrical.as_time_code(), None);
+        assert_eq!(time_code.as_ticks_per_quarter(), None);
+        assert_eq!(time_code.as_time_code(), Some((25.0, 40)));
+    }
+}
@@ -8,7 +8,8 @@ use derive_more::{Debug, Display, Eq, Error, PartialEq};
 ///
 /// Also, more parameters may be added to the MThd chunk in the future: it
 /// is important to read and honor the length, even if it is longer than 6.
-#[derive(Debug, Display, PartialEq, Eq)]
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[non_exhaustive]
 pub enum Format {
     /// The file contains a single multi-channel track.
@@ -21,11 +22,36 @@ pub enum Format {
     SequentiallyIndependentSingleTrackPatterns,
 }
 
+impl Format {
+    /// Whether a file of this [`Format`] may contain `count` track chunks.
+    ///
+    /// A [`Format::SingleMultiChannelTrack`] file must contain exactly one.
+    /// The other formats allow any number, so that a file can be built up
+    /// one track at a time.
+    #[inline]
+    pub fn is_valid_track_count(&self, count: u16) -> bool {
+        match self {
+            Format::SingleMultiChannelTrack => count == 1,
+            Format::SimultaneousTracks | Format::SequentiallyIndependentSingleTrackPatterns => true,
+        }
+    }
+}
+
 #[derive(Debug, Display, Error)]
 pub enum TryFromError {
     UnknownFormatBytes,
 }
 
+impl From<&Format> for [u8; 2] {
+    fn from(value: &Format) -> Self {
+        match value {
+            Format::SingleMultiChannelTrack => [0x00, 0x00],
+            Format::SimultaneousTracks => [0x00, 0x01],
+            Format::SequentiallyIndependentSingleTrackPatterns => [0x00, 0x02],
+        }
+    }
+}
+
 impl TryFrom<&[u8; 2]> for Format {
     type Error = TryFromError;
 
@@ -38,3 +64,38 @@ impl TryFrom<&[u8; 2]> for Format {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_bytes() {
+        for format in [
+            Format::SingleMultiChannelTrack,
+            Format::SimultaneousTracks,
+            Format::SequentiallyIndependentSingleTrackPatterns,
+        ] {
+            let bytes = <[u8; 2]>::from(&format);
+
+            assert_eq!(Format::try_from(&bytes).unwrap(), format);
+        }
+    }
+
+    #[test]
+    fn unknown_bytes_are_an_error() {
+        for bytes in [[0x00, 0x03], [0x01, 0x00], [0xFF, 0xFF]] {
+            assert!(matches!(
+                Format::try_from(&bytes),
+                Err(TryFromError::UnknownFormatBytes)
+            ));
+        }
+    }
+
+    #[test]
+    fn is_valid_track_count() {
+        assert!(Format::SingleMultiChannelTrack.is_valid_track_count(1));
+        assert!(!Format::SingleMultiChannelTrack.is_valid_track_count(2));
+        assert!(Format::SimultaneousTracks.is_valid_track_count(5));
+    }
+}
@@ -1,21 +1,28 @@
 pub mod division;
 pub mod format;
 
+use ::core::hash::Hasher;
+use alloc::vec::Vec;
+
 use derive_more::{Debug, Display, Error};
 
 use crate::{
     core::chunk::header::{division::Division, format::Format},
-    file::chunk::header::HeaderChunkFile,
+    file::chunk::header::{HEADER_CHUNK_KIND, HEADER_CHUNK_LENGTH, HeaderChunkFile},
 };
 
 /// The [`HeaderChunk`] at the beginning of the file specifies some basic
 /// information about the data in the file.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HeaderChunk {
     /// Specifies the overall organization of the file.
     pub format: Format,
 
     /// The number of track chunks in the file.
+    ///
+    /// Editing the tracks of a [`MIDI`](crate::core::midi::MIDI) through its
+    /// methods keeps this in sync, but setting it directly does not, see
+    /// [`MIDI::sync_track_count`](crate::core::midi::MIDI::sync_track_count).
     pub tracks_count: u16,
 
     /// Specifies the meaning of the delta-times.
@@ -29,6 +36,44 @@ pub enum TryFromError {
     InvalidTracksCount,
 }
 
+#[derive(Debug, Display, Error)]
+pub enum NewError {
+    InvalidTracksCount,
+}
+
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    InvalidDivision,
+}
+
+impl HeaderChunk {
+    /// Creates a new [`HeaderChunk`], checking that `tracks_count` is allowed
+    /// by `format`, see [`Format::is_valid_track_count`].
+    pub fn new(format: Format, tracks_count: u16, division: Division) -> Result<Self, NewError> {
+        if !format.is_valid_track_count(tracks_count) {
+            return Err(NewError::InvalidTracksCount);
+        }
+
+        Ok(HeaderChunk {
+            format,
+            tracks_count,
+            division,
+        })
+    }
+}
+
+impl Default for HeaderChunk {
+    /// A [`Format::SingleMultiChannelTrack`] header declaring its one track,
+    /// with 480 ticks per quarter-note, a common resolution.
+    fn default() -> Self {
+        HeaderChunk {
+            format: Format::SingleMultiChannelTrack,
+            tracks_count: 1,
+            division: Division::TicksPerQuarterNote(480),
+        }
+    }
+}
+
 impl TryFrom<&HeaderChunkFile<'_>> for HeaderChunk {
     type Error = TryFromError;
 
@@ -38,14 +83,100 @@ impl TryFrom<&HeaderChunkFile<'_>> for HeaderChunk {
         let division =
             Division::try_from(*value.division).map_err(|_| TryFromError::InvalidDivision)?;
 
-        if format == Format::SingleMultiChannelTrack && tracks_count != 1 {
-            return Err(TryFromError::InvalidTracksCount);
+        HeaderChunk::new(format, tracks_count, division)
+            .map_err(|_| TryFromError::InvalidTracksCount)
+    }
+}
+
+impl HeaderChunk {
+    /// A short description of the [`Format`] of this [`HeaderChunk`], worded
+    /// the same for every tool displaying it, such as
+    /// `"SMF Type 1 (simultaneous tracks)"`.
+    pub fn format_description(&self) -> &'static str {
+        match self.format {
+            Format::SingleMultiChannelTrack => "SMF Type 0 (single track)",
+            Format::SimultaneousTracks => "SMF Type 1 (simultaneous tracks)",
+            Format::SequentiallyIndependentSingleTrackPatterns => {
+                "SMF Type 2 (independent patterns)"
+            }
         }
+    }
 
-        Ok(HeaderChunk {
-            format,
-            tracks_count,
-            division,
-        })
+    /// The number of bytes this [`HeaderChunk`] takes in a file, including
+    /// its kind and length.
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        8 + *HEADER_CHUNK_LENGTH as usize
+    }
+}
+
+impl HeaderChunk {
+    /// Feeds this [`HeaderChunk`] to `hasher` in an encoding that does not
+    /// depend on the platform, even if its division cannot be written.
+    pub(crate) fn hash_content(&self, hasher: &mut impl Hasher) {
+        hasher.write(HEADER_CHUNK_KIND);
+        hasher.write(&<[u8; 2]>::from(&self.format));
+        hasher.write(&self.tracks_count.to_be_bytes());
+        match self.division {
+            Division::TicksPerQuarterNote(ticks) => {
+                hasher.write(&[0]);
+                hasher.write(&ticks.to_be_bytes());
+            }
+            Division::TimeCode {
+                frames_per_second,
+                ticks_per_frame,
+            } => hasher.write(&[1, frames_per_second as i8 as u8, ticks_per_frame]),
+        }
+    }
+}
+
+impl TryFrom<&HeaderChunk> for Vec<u8> {
+    type Error = EncodeError;
+
+    fn try_from(value: &HeaderChunk) -> Result<Self, Self::Error> {
+        let division =
+            <[u8; 2]>::try_from(&value.division).map_err(|_| EncodeError::InvalidDivision)?;
+
+        let mut bytes = Vec::with_capacity(value.byte_len());
+        bytes.extend(HEADER_CHUNK_KIND);
+        bytes.extend(HEADER_CHUNK_LENGTH.to_be_bytes());
+        bytes.extend(<[u8; 2]>::from(&value.format));
+        bytes.extend(value.tracks_count.to_be_bytes());
+        bytes.extend(division);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_description_of_each_format() {
+        for (format, description) in [
+            (Format::SingleMultiChannelTrack, "SMF Type 0 (single track)"),
+            (
+                Format::SimultaneousTracks,
+                "SMF Type 1 (simultaneous tracks)",
+            ),
+            (
+                Format::SequentiallyIndependentSingleTrackPatterns,
+                "SMF Type 2 (independent patterns)",
+            ),
+        ] {
+            let header_chunk =
+                HeaderChunk::new(format, 1, Division::TicksPerQuarterNote(96)).unwrap();
+
+            assert_eq!(header_chunk.format_description(), description);
+        }
+    }
+
+    #[test]
+    fn default_is_single_track_at_480_ticks_per_quarter_note() {
+        let header_chunk = HeaderChunk::default();
+
+        assert_eq!(header_chunk.format, Format::SingleMultiChannelTrack);
+        assert_eq!(header_chunk.tracks_count, 1);
+        assert_eq!(header_chunk.division, Division::TicksPerQuarterNote(480));
     }
 }
@@ -1,18 +1,20 @@
 pub mod header;
 pub mod track;
 
+use alloc::{string::String, vec::Vec};
+
 use derive_more::{Debug, Display, Error};
 
 use crate::{
     core::chunk::{header::HeaderChunk, track::TrackChunk},
     file::{
         chunk::{
-            ChunkFile,
-            header::{HEADER_CHUNK_KIND, HeaderChunkFile},
-            track::{TRACK_CHUNK_KIND, TrackChunkFile},
+            ChunkFile, ChunksFile, TypedChunkFile, TypedError, header::HEADER_CHUNK_KIND,
+            track::TRACK_CHUNK_KIND,
         },
         event::track::TrackEventsFile,
     },
+    options::ParseOptions,
 };
 
 /// [`MIDI`](crate::core::midi::MIDI) contains three types of [`Chunk`]:
@@ -20,7 +22,7 @@ use crate::{
 /// - [`Chunk::Header`] with [`HeaderChunk`] containing MIDI metadata
 /// - [`Chunk::Track`] with [`TrackChunk`] containing MIDI event data
 /// - [`Chunk::Alien`] with [`AlienChunk`] for unrecognized chunk types
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Chunk {
     /// Provides a minimal amount of information pertaining to the entire
     /// [MIDI](crate::core::midi::MIDI).
@@ -37,10 +39,17 @@ pub enum Chunk {
 
 /// An unrecognized chunk type, which your program should ignore.
 /// It is simply the owned version of [`ChunkFile`].
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AlienChunk {
     pub kind: [u8; 4],
+
+    /// The length declared in the file. It is larger than the length of
+    /// `data` if the chunk ran past the end of the file and was recovered,
+    /// see [`ParseOptions::recover_chunk_lengths`], and may disagree with
+    /// `data` if either is modified, so writing an [`AlienChunk`] always uses
+    /// the length of `data` instead.
     pub length: u32,
+
     pub data: Vec<u8>,
 }
 
@@ -63,28 +72,203 @@ pub enum TryFromError {
     TrackEventsFileToTrackChunk(crate::core::event::TryFromError),
 }
 
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    HeaderChunkToBytes(crate::core::chunk::header::EncodeError),
+    TrackChunkToBytes(crate::core::chunk::track::EncodeError),
+}
+
 impl<'a> TryFrom<&'a ChunkFile<'a>> for Chunk {
     type Error = TryFromError;
 
     fn try_from(value: &'a ChunkFile<'a>) -> Result<Self, Self::Error> {
-        match value.kind {
-            HEADER_CHUNK_KIND => {
-                let chunk_file = HeaderChunkFile::try_from(value)
-                    .map_err(TryFromError::ChunkFileToHeaderChunkFile)?;
+        Chunk::try_from_with_options(value, &ParseOptions::default())
+    }
+}
+
+impl Chunk {
+    /// Converts a [`ChunkFile`] like `Chunk::try_from`, following `options`.
+    pub fn try_from_with_options(
+        value: &ChunkFile,
+        options: &ParseOptions,
+    ) -> Result<Self, TryFromError> {
+        let typed_chunk_file = TypedChunkFile::try_from(value).map_err(|error| match error {
+            TypedError::ChunkFileToHeaderChunkFile(error) => {
+                TryFromError::ChunkFileToHeaderChunkFile(error)
+            }
+            TypedError::ChunkFileToTrackChunkFile(error) => {
+                TryFromError::ChunkFileToTrackChunkFile(error)
+            }
+        })?;
+        match typed_chunk_file {
+            TypedChunkFile::Header(chunk_file) => {
                 let header_chunk = HeaderChunk::try_from(&chunk_file)
                     .map_err(TryFromError::HeaderChunkFileToHeaderChunk)?;
                 Ok(Chunk::Header(header_chunk))
             }
-            TRACK_CHUNK_KIND => {
-                let chunk_file = TrackChunkFile::try_from(value)
-                    .map_err(TryFromError::ChunkFileToTrackChunkFile)?;
-                let events_file = TrackEventsFile::try_from(&chunk_file)
+            TypedChunkFile::Track(chunk_file) => {
+                let events_file = TrackEventsFile::try_from_with_options(&chunk_file, options)
                     .map_err(TryFromError::TrackChunkFileToTrackEventsFile)?;
-                let track_chunk = TrackChunk::try_from(&events_file)
+                let track_chunk = TrackChunk::try_from_with_options(&events_file, options)
                     .map_err(TryFromError::TrackEventsFileToTrackChunk)?;
                 Ok(Chunk::Track(track_chunk))
             }
-            _ => Ok(Chunk::Alien(AlienChunk::from(*value))),
+            TypedChunkFile::Alien(chunk_file) => Ok(Chunk::Alien(AlienChunk::from(chunk_file))),
+        }
+    }
+}
+
+impl Chunk {
+    /// The number of bytes this [`Chunk`] takes in a file, including its kind
+    /// and length.
+    pub fn byte_len(&self) -> usize {
+        match self {
+            Chunk::Header(header_chunk) => header_chunk.byte_len(),
+            Chunk::Track(track_chunk) => track_chunk.byte_len(),
+            Chunk::Alien(alien_chunk) => alien_chunk.byte_len(),
+        }
+    }
+}
+
+impl AlienChunk {
+    /// The number of bytes this [`AlienChunk`] takes in a file, including its
+    /// kind and length, that is, `8 + data.len()`.
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        8 + self.data.len()
+    }
+}
+
+impl From<&AlienChunk> for Vec<u8> {
+    fn from(value: &AlienChunk) -> Self {
+        let mut bytes = Vec::with_capacity(value.byte_len());
+        bytes.extend(value.kind);
+        bytes.extend(((value.byte_len() - 8) as u32).to_be_bytes());
+        bytes.extend(&value.data);
+        bytes
+    }
+}
+
+impl TryFrom<&Chunk> for Vec<u8> {
+    type Error = EncodeError;
+
+    fn try_from(value: &Chunk) -> Result<Self, Self::Error> {
+        match value {
+            Chunk::Header(header_chunk) => {
+                Vec::try_from(header_chunk).map_err(EncodeError::HeaderChunkToBytes)
+            }
+            Chunk::Track(track_chunk) => {
+                Vec::try_from(track_chunk).map_err(EncodeError::TrackChunkToBytes)
+            }
+            Chunk::Alien(alien_chunk) => Ok(Vec::from(alien_chunk)),
+        }
+    }
+}
+
+/// The kind of a [`Chunk`], as read from its first 4 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkKind {
+    /// `MThd`, see [`Chunk::Header`].
+    Header,
+
+    /// `MTrk`, see [`Chunk::Track`].
+    Track,
+
+    /// Any other kind, see [`Chunk::Alien`].
+    #[debug("Alien({:?})", String::from_utf8_lossy(_0))]
+    Alien([u8; 4]),
+}
+
+impl From<&[u8; 4]> for ChunkKind {
+    fn from(value: &[u8; 4]) -> Self {
+        match value {
+            HEADER_CHUNK_KIND => ChunkKind::Header,
+            TRACK_CHUNK_KIND => ChunkKind::Track,
+            _ => ChunkKind::Alien(*value),
+        }
+    }
+}
+
+/// Where a chunk lies in a file, without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkInfo {
+    pub kind: ChunkKind,
+
+    /// The length of the chunk's data as declared in the file, excluding its
+    /// kind and length.
+    pub length: u32,
+
+    /// The index of the first byte of the chunk, its kind, in the file.
+    pub offset: usize,
+}
+
+impl From<&ChunkFile<'_>> for ChunkInfo {
+    fn from(value: &ChunkFile) -> Self {
+        ChunkInfo {
+            kind: ChunkKind::from(value.kind),
+            length: value.length,
+            offset: value.offset,
+        }
+    }
+}
+
+/// Lists the chunks of the file `bytes` without parsing their data, the
+/// cheapest pass over a file.
+///
+/// Unlike [`MIDI`](crate::core::midi::MIDI), a header or track chunk with
+/// malformed data is listed as well.
+pub fn chunk_infos(bytes: &[u8]) -> Result<Vec<ChunkInfo>, crate::file::chunk::TryFromError> {
+    let chunks_file = ChunksFile::try_from_with_options(bytes, &ParseOptions::default())?;
+    Ok(chunks_file.iter().map(ChunkInfo::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn alien_chunk_is_written_with_length_of_data() {
+        let chunk = Chunk::Alien(AlienChunk {
+            kind: *b"XFIH",
+            length: 99,
+            data: vec![1, 2, 3],
+        });
+
+        assert_eq!(chunk.byte_len(), 11);
+        assert_eq!(
+            Vec::try_from(&chunk).unwrap(),
+            b"XFIH\0\0\0\x03\x01\x02\x03"
+        );
+    }
+
+    #[test]
+    fn chunk_infos_offsets_point_at_each_chunk() {
+        let mut bytes = include_bytes!("../../../../../assets/Lapis Lazuli.mid").to_vec();
+        let alien_offset = bytes.len();
+        bytes.extend(b"XFIH\0\0\0\x01z");
+
+        let chunk_infos = chunk_infos(&bytes).unwrap();
+
+        assert_eq!(chunk_infos[0].kind, ChunkKind::Header);
+        assert_eq!(chunk_infos[0].offset, 0);
+        for chunk_info in &chunk_infos {
+            let kind: [u8; 4] = bytes[chunk_info.offset..chunk_info.offset + 4]
+                .try_into()
+                .unwrap();
+            assert_eq!(ChunkKind::from(&kind), chunk_info.kind);
+        }
+        for pair in chunk_infos.windows(2) {
+            assert_eq!(pair[1].offset, pair[0].offset + 8 + pair[0].length as usize);
         }
+        assert_eq!(
+            chunk_infos.last(),
+            Some(&ChunkInfo {
+                kind: ChunkKind::Alien(*b"XFIH"),
+                length: 1,
+                offset: alien_offset,
+            })
+        );
     }
 }
@@ -0,0 +1,128 @@
+use alloc::vec::Vec;
+
+use derive_more::{Debug, Display, Error};
+
+use crate::core::{
+    chunk::track::{TrackChunk, TrackEvent},
+    event::{Event, meta::MetaEvent},
+    ticks::DeltaTicks,
+};
+
+/// Assembles a [`TrackChunk`] from events placed at absolute ticks.
+///
+/// Events may be pushed in any order; [`TrackBuilder::build`] sorts them by
+/// tick, keeping the push order of simultaneous events, computes their
+/// delta-times and terminates the track with [`MetaEvent::EndOfTrack`].
+#[derive(Debug, Default)]
+pub struct TrackBuilder {
+    events: Vec<(u64, Event)>,
+}
+
+#[derive(Debug, Display, Error)]
+pub enum BuildError {
+    /// Two consecutive events are further apart than [`DeltaTicks::MAX`].
+    DeltaTimeOverflow,
+}
+
+impl TrackBuilder {
+    /// Creates a new, empty `TrackBuilder`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Places `kind` at the absolute `tick` of the track.
+    ///
+    /// Pushing [`MetaEvent::EndOfTrack`] does not end the track early: it
+    /// only ensures the track lasts at least until `tick`.
+    pub fn push_at(&mut self, tick: u64, kind: Event) -> &mut Self {
+        self.events.push((tick, kind));
+        self
+    }
+
+    /// Builds the [`TrackChunk`].
+    pub fn build(mut self) -> Result<TrackChunk, BuildError> {
+        self.events.sort_by_key(|(tick, _)| *tick);
+        let end_tick = self.events.last().map_or(0, |(tick, _)| *tick);
+
+        let mut track_events = Vec::with_capacity(self.events.len() + 1);
+        let mut previous_tick = 0;
+        for (tick, kind) in self.events {
+            if matches!(kind, Event::Meta(MetaEvent::EndOfTrack)) {
+                continue;
+            }
+            let delta_time =
+                DeltaTicks::new(tick - previous_tick).ok_or(BuildError::DeltaTimeOverflow)?;
+            previous_tick = tick;
+            track_events.push(TrackEvent { delta_time, kind });
+        }
+
+        track_events.push(TrackEvent {
+            delta_time: DeltaTicks::new(end_tick - previous_tick)
+                .ok_or(BuildError::DeltaTimeOverflow)?,
+            kind: Event::Meta(MetaEvent::EndOfTrack),
+        });
+
+        Ok(TrackChunk(track_events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::core::event::channel::{Channel, ChannelEvent, ChannelMessage};
+
+    fn note_on(key: u8) -> Event {
+        Event::Channel(ChannelEvent {
+            channel: Channel::new(0).unwrap(),
+            message: ChannelMessage::NoteOn { key, velocity: 64 },
+        })
+    }
+
+    #[test]
+    fn build_keeps_chord_at_same_tick() {
+        let mut track_builder = TrackBuilder::new();
+        track_builder
+            .push_at(96, note_on(72))
+            .push_at(0, note_on(60))
+            .push_at(0, note_on(64))
+            .push_at(200, Event::Meta(MetaEvent::EndOfTrack));
+
+        let track_chunk = track_builder.build().unwrap();
+
+        assert_eq!(
+            track_chunk.0,
+            vec![
+                TrackEvent {
+                    delta_time: DeltaTicks(0),
+                    kind: note_on(60),
+                },
+                TrackEvent {
+                    delta_time: DeltaTicks(0),
+                    kind: note_on(64),
+                },
+                TrackEvent {
+                    delta_time: DeltaTicks(96),
+                    kind: note_on(72),
+                },
+                TrackEvent {
+                    delta_time: DeltaTicks(104),
+                    kind: Event::Meta(MetaEvent::EndOfTrack),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn build_rejects_delta_time_overflow() {
+        let mut track_builder = TrackBuilder::new();
+        track_builder.push_at(u64::from(DeltaTicks::MAX.0) + 1, note_on(60));
+
+        assert!(matches!(
+            track_builder.build(),
+            Err(BuildError::DeltaTimeOverflow)
+        ));
+    }
+}
@@ -1,11 +1,33 @@
-use derive_more::{Debug, Deref, IntoIterator};
+pub mod builder;
+
+use ::core::hash::Hasher;
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    format,
+    string::String,
+    vec::Vec,
+};
+
+use derive_more::{Debug, Deref, Display, Error, Index, IntoIterator};
+use log::debug;
 
 use crate::{
-    core::event::{Event, TryFromError},
-    file::event::track::TrackEventsFile,
+    core::{
+        event::{
+            Event, TryFromError,
+            channel::{Channel, ChannelEvent, ChannelMessage},
+            controller::Controller,
+            meta::MetaEvent,
+            sysex::SplitSysEx,
+        },
+        ticks::{AbsoluteTicks, DeltaTicks},
+    },
+    file::{chunk::track::TRACK_CHUNK_KIND, event::track::TrackEventsFile},
+    options::{AnalysisOptions, ParseOptions},
+    writer::{push_variable_length_quantity, variable_length_quantity_len},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TrackEvent {
     /// Represents the amount of time before the following event, stored as a
     /// variable-length quantity.
@@ -18,7 +40,7 @@ pub struct TrackEvent {
     /// times _aren't_ zero.)
     ///
     /// Delta-time is in ticks as specified in the header chunk.
-    pub delta_time: u32,
+    pub delta_time: DeltaTicks,
 
     pub kind: Event,
 }
@@ -29,19 +51,1194 @@ pub struct TrackEvent {
 ///
 /// The format for Track Chunks (described below) is exactly the same for all
 /// three formats (0, 1, and 2: see "Header Chunk" above) of MIDI Files.
-#[derive(Debug, Deref, IntoIterator)]
-pub struct TrackChunk(Vec<TrackEvent>);
+#[derive(Debug, Clone, PartialEq, Eq, Deref, Index, IntoIterator)]
+#[into_iterator(owned, ref)]
+pub struct TrackChunk(pub(crate) Vec<TrackEvent>);
+
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    /// A delta-time is larger than [`DeltaTicks::MAX`], so it does not fit in
+    /// a variable-length quantity.
+    DeltaTimeOverflow,
+
+    EventToBytes(crate::core::event::EncodeError),
+
+    /// The events take more bytes than the length of a chunk can hold.
+    ChunkTooLong,
+}
+
+#[derive(Debug, Display, Error)]
+pub enum EditError {
+    /// An event would be further than [`DeltaTicks::MAX`] from the previous
+    /// one, so the track could not be written.
+    DeltaTimeOverflow,
+}
 
 impl<'a> TryFrom<&'a TrackEventsFile<'a>> for TrackChunk {
     type Error = TryFromError;
 
     fn try_from(value: &TrackEventsFile) -> Result<Self, Self::Error> {
-        let mut track_events = Vec::new();
+        TrackChunk::try_from_with_options(value, &ParseOptions::default())
+    }
+}
+
+impl TrackChunk {
+    /// Converts a [`TrackEventsFile`] like `TrackChunk::try_from`, following
+    /// `options`.
+    pub fn try_from_with_options(
+        value: &TrackEventsFile,
+        options: &ParseOptions,
+    ) -> Result<Self, TryFromError> {
+        let mut track_events = Vec::with_capacity(value.len());
+        let mut split_sys_ex = SplitSysEx::default();
         for track_event_file in value.iter() {
-            let delta_time = track_event_file.delta_time;
-            let kind = Event::try_from(&track_event_file.event)?;
+            let delta_time = DeltaTicks(track_event_file.delta_time);
+            let mut kind = Event::try_from_with_options(&track_event_file.event, options)?;
+            if let Event::SysEx(sys_ex_event) = &mut kind {
+                split_sys_ex.classify(sys_ex_event);
+            }
             track_events.push(TrackEvent { delta_time, kind });
         }
+
+        let ends_with_end_of_track = matches!(
+            track_events.last(),
+            Some(TrackEvent {
+                kind: Event::Meta(MetaEvent::EndOfTrack),
+                ..
+            })
+        );
+        if options.synthesize_end_of_track && !ends_with_end_of_track {
+            debug!("Track chunk does not end with EndOfTrack. Synthesizing one.");
+            track_events.push(TrackEvent {
+                delta_time: DeltaTicks(0),
+                kind: Event::Meta(MetaEvent::EndOfTrack),
+            });
+        }
+
         Ok(TrackChunk(track_events))
     }
+
+    /// The number of [`TrackEvent`]s in this [`TrackChunk`].
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether this [`TrackChunk`] has no [`TrackEvent`]s.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The [`TrackEvent`] at `index`, or `None` if out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&TrackEvent> {
+        self.0.get(index)
+    }
+
+    /// Iterates over the [`TrackEvent`]s of this [`TrackChunk`] along with
+    /// their absolute time in ticks, that is, the sum of all delta-times up to
+    /// and including their own.
+    pub fn absolute_events(&self) -> impl Iterator<Item = (AbsoluteTicks, &TrackEvent)> {
+        self.iter().scan(AbsoluteTicks(0), |tick, track_event| {
+            *tick += track_event.delta_time;
+            Some((*tick, track_event))
+        })
+    }
+
+    /// Iterates over the [`ChannelEvent`]s of this [`TrackChunk`] along with
+    /// their delta-time, skipping meta and system exclusive events.
+    ///
+    /// A delta-time is still relative to the previous event of any kind, see
+    /// [`TrackChunk::absolute_events`] for times that do not depend on the
+    /// skipped events.
+    pub fn channel_events(&self) -> impl Iterator<Item = (DeltaTicks, &ChannelEvent)> {
+        self.iter()
+            .filter_map(|track_event| match &track_event.kind {
+                Event::Channel(channel_event) => Some((track_event.delta_time, channel_event)),
+                Event::Meta(_) | Event::SysEx(_) => None,
+            })
+    }
+
+    /// The length of this [`TrackChunk`] in ticks, that is, the absolute time
+    /// of its last event, usually [`MetaEvent::EndOfTrack`].
+    pub fn duration_ticks(&self) -> u64 {
+        self.iter()
+            .map(|track_event| u64::from(track_event.delta_time.0))
+            .sum()
+    }
+
+    /// Pairs every [`TrackEvent`] of this [`TrackChunk`] with the [`Channel`]
+    /// it is associated with, if any.
+    ///
+    /// A channel event is associated with its own channel. A
+    /// [`MetaEvent::MIDIChannelPrefix`] associates itself and the following
+    /// meta and system exclusive events with its channel, until the next
+    /// channel event.
+    pub fn with_effective_channel(&self) -> Vec<(Option<Channel>, &TrackEvent)> {
+        let mut channel_prefix: Option<Channel> = None;
+        self.iter()
+            .map(|track_event| {
+                let channel = match &track_event.kind {
+                    Event::Channel(channel_event) => {
+                        channel_prefix = None;
+                        Some(channel_event.channel)
+                    }
+                    Event::Meta(MetaEvent::MIDIChannelPrefix(channel)) => {
+                        channel_prefix = Channel::new(*channel);
+                        channel_prefix
+                    }
+                    Event::Meta(_) | Event::SysEx(_) => channel_prefix,
+                };
+                (channel, track_event)
+            })
+            .collect()
+    }
+
+    /// Returns a copy of this [`TrackChunk`] with every delta-time multiplied
+    /// by `numerator / denominator`.
+    ///
+    /// Rescaling is done on absolute times so that rounding errors do not
+    /// accumulate along the track. Returns `None` if `denominator` is zero or
+    /// a rescaled delta-time exceeds [`DeltaTicks::MAX`].
+    pub(crate) fn rescaled(&self, numerator: u64, denominator: u64) -> Option<TrackChunk> {
+        if denominator == 0 {
+            return None;
+        }
+
+        let mut track_events = Vec::with_capacity(self.0.len());
+        let mut tick: u64 = 0;
+        let mut rescaled_tick: u64 = 0;
+        for track_event in self.iter() {
+            tick += u64::from(track_event.delta_time.0);
+            let next_tick =
+                tick.checked_mul(numerator)?.checked_add(denominator / 2)? / denominator;
+            let delta_time = DeltaTicks::new(next_tick - rescaled_tick)?;
+            rescaled_tick = next_tick;
+            track_events.push(TrackEvent {
+                delta_time,
+                kind: track_event.kind.clone(),
+            });
+        }
+        Some(TrackChunk(track_events))
+    }
+
+    /// Appends the events of `other` after those of this [`TrackChunk`],
+    /// delayed so that `other` starts at the absolute `tick`, which should be
+    /// no earlier than the end of this [`TrackChunk`].
+    ///
+    /// The final [`MetaEvent::EndOfTrack`] of this [`TrackChunk`], if any, is
+    /// dropped so that only that of `other` remains. Does nothing if `other`
+    /// is empty.
+    ///
+    /// Fails with [`EditError::DeltaTimeOverflow`], leaving this
+    /// [`TrackChunk`] unchanged, if the first event of `other` would be
+    /// further than [`DeltaTicks::MAX`] from the last one kept.
+    pub(crate) fn append_at(&mut self, other: &TrackChunk, tick: u64) -> Result<(), EditError> {
+        let Some((first, rest)) = other.split_first() else {
+            return Ok(());
+        };
+
+        let kept = self.without_end_of_track().len();
+        let kept_ticks: u64 = self.0[..kept]
+            .iter()
+            .map(|track_event| u64::from(track_event.delta_time.0))
+            .sum();
+        // The delta-time of the dropped `EndOfTrack` is kept, so that `other`
+        // never starts before this track used to end.
+        let start_tick = tick.max(self.duration_ticks());
+        let delta_time = DeltaTicks::new(u64::from(first.delta_time.0) + start_tick - kept_ticks)
+            .ok_or(EditError::DeltaTimeOverflow)?;
+
+        self.0.truncate(kept);
+        self.0.push(TrackEvent {
+            delta_time,
+            kind: first.kind.clone(),
+        });
+        self.0.extend_from_slice(rest);
+        Ok(())
+    }
+}
+
+impl TrackChunk {
+    /// Shifts the key of every note event (`NoteOff`, `NoteOn` and
+    /// `PolyphonicKeyPressure`) by `semitones`.
+    ///
+    /// Keys that would leave `0..=127` are clamped into it. Events on the
+    /// channels excluded by `options`, usually
+    /// [`DRUM_CHANNEL`](crate::core::event::channel::DRUM_CHANNEL), are left
+    /// untouched.
+    pub fn transpose(&mut self, semitones: i8, options: &AnalysisOptions) {
+        for track_event in self.0.iter_mut() {
+            let Event::Channel(channel_event) = &mut track_event.kind else {
+                continue;
+            };
+            if !options.includes(channel_event.channel) {
+                continue;
+            }
+            match &mut channel_event.message {
+                ChannelMessage::NoteOff { key, .. }
+                | ChannelMessage::NoteOn { key, .. }
+                | ChannelMessage::PolyphonicKeyPressure { key, .. } => {
+                    *key = (i16::from(*key) + i16::from(semitones)).clamp(0, 127) as u8;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Multiplies the velocity of every `NoteOn` by `factor`, rounding to the
+    /// nearest, see [`TrackChunk::map_velocity`].
+    pub fn scale_velocity(&mut self, factor: f64) {
+        // `as` saturates, and adding a half rounds to the nearest without
+        // `f64::round`, which `core` lacks.
+        self.map_velocity(|velocity| (f64::from(velocity) * factor + 0.5) as u8);
+    }
+
+    /// Replaces the velocity of every `NoteOn` with `f` of it, such as to
+    /// compress dynamics with a curve.
+    ///
+    /// A `NoteOn` of velocity zero is a `NoteOff`, so it is left untouched,
+    /// and new velocities are clamped into `1..=127` so that no `NoteOn`
+    /// becomes one.
+    pub fn map_velocity(&mut self, f: impl Fn(u8) -> u8) {
+        for track_event in self.0.iter_mut() {
+            if let Event::Channel(ChannelEvent {
+                message: ChannelMessage::NoteOn { velocity, .. },
+                ..
+            }) = &mut track_event.kind
+                && *velocity != 0
+            {
+                *velocity = f(*velocity).clamp(1, 127);
+            }
+        }
+    }
+
+    /// Snaps the absolute time of every event to the nearest multiple of
+    /// `grid_ticks`, rounding halfway ticks up, and recomputes delta-times.
+    ///
+    /// Rounding never moves an event past a later one, so the order of events,
+    /// including simultaneous ones, is preserved. Does nothing if `grid_ticks`
+    /// is zero.
+    ///
+    /// Fails with [`EditError::DeltaTimeOverflow`], leaving this
+    /// [`TrackChunk`] untouched, if rounding moves two events too far apart.
+    pub fn quantize(&mut self, grid_ticks: u32) -> Result<(), EditError> {
+        if grid_ticks == 0 {
+            return Ok(());
+        }
+
+        let grid_ticks = u64::from(grid_ticks);
+        let mut delta_times = Vec::with_capacity(self.0.len());
+        let mut quantized_tick: u64 = 0;
+        for (AbsoluteTicks(tick), _) in self.absolute_events() {
+            let next_tick = tick.saturating_add(grid_ticks / 2) / grid_ticks * grid_ticks;
+            delta_times.push(
+                DeltaTicks::new(next_tick - quantized_tick).ok_or(EditError::DeltaTimeOverflow)?,
+            );
+            quantized_tick = next_tick;
+        }
+
+        for (track_event, delta_time) in self.0.iter_mut().zip(delta_times) {
+            track_event.delta_time = delta_time;
+        }
+        Ok(())
+    }
+
+    /// Moves every channel voice event and [`MetaEvent::MIDIChannelPrefix`]
+    /// of channel `from` to channel `to`.
+    pub fn remap_channel(&mut self, from: Channel, to: Channel) {
+        for track_event in self.0.iter_mut() {
+            match &mut track_event.kind {
+                Event::Channel(ChannelEvent { channel, .. }) if *channel == from => {
+                    *channel = to;
+                }
+                Event::Meta(MetaEvent::MIDIChannelPrefix(channel))
+                    if *channel == u8::from(from) =>
+                {
+                    *channel = u8::from(to);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns a copy of this [`TrackChunk`] keeping only the channel voice
+    /// events of `channel`, along with every meta and system exclusive event.
+    ///
+    /// Delta-times are recomputed so that kept events stay at their absolute
+    /// time. Fails with [`EditError::DeltaTimeOverflow`] if dropped events
+    /// leave two kept ones too far apart.
+    pub fn filter_to_channel(&self, channel: Channel) -> Result<TrackChunk, EditError> {
+        self.filtered(|kind| match kind {
+            Event::Channel(channel_event) => channel_event.channel == channel,
+            Event::Meta(_) | Event::SysEx(_) => true,
+        })
+    }
+
+    /// Returns a copy of this [`TrackChunk`] keeping only what a score needs,
+    /// that is, exactly:
+    ///
+    /// - `NoteOn` and `NoteOff` channel events,
+    /// - [`MetaEvent::SetTempo`], [`MetaEvent::TimeSignature`] and
+    ///   [`MetaEvent::EndOfTrack`].
+    ///
+    /// Every other event is dropped, including control changes, program
+    /// changes, pitch bends, key and channel pressure, other meta-events and
+    /// system exclusive events. Delta-times are recomputed so that kept events
+    /// stay at their absolute time. Fails with [`EditError::DeltaTimeOverflow`]
+    /// if dropped events leave two kept ones too far apart.
+    pub fn notes_only(&self) -> Result<TrackChunk, EditError> {
+        self.filtered(|kind| {
+            matches!(
+                kind,
+                Event::Channel(ChannelEvent {
+                    message: ChannelMessage::NoteOn { .. } | ChannelMessage::NoteOff { .. },
+                    ..
+                }) | Event::Meta(
+                    MetaEvent::SetTempo(_)
+                        | MetaEvent::TimeSignature { .. }
+                        | MetaEvent::EndOfTrack
+                )
+            )
+        })
+    }
+
+    /// Returns a copy of this [`TrackChunk`] keeping only the events whose
+    /// kind is `kept`, with delta-times recomputed so that they stay at their
+    /// absolute time.
+    fn filtered(&self, kept: impl Fn(&Event) -> bool) -> Result<TrackChunk, EditError> {
+        let mut track_events = Vec::new();
+        let mut previous_tick: u64 = 0;
+        for (AbsoluteTicks(tick), track_event) in self.absolute_events() {
+            if !kept(&track_event.kind) {
+                continue;
+            }
+            track_events.push(TrackEvent {
+                delta_time: DeltaTicks::new(tick - previous_tick)
+                    .ok_or(EditError::DeltaTimeOverflow)?,
+                kind: track_event.kind.clone(),
+            });
+            previous_tick = tick;
+        }
+        Ok(TrackChunk(track_events))
+    }
+
+    /// The program changes of `channel` along with the bank they select, as
+    /// `(absolute_tick, bank, program)`.
+    ///
+    /// The bank is the 14-bit combination `msb << 7 | lsb` of the latest
+    /// [`Controller::BANK_SELECT_MSB`] and [`Controller::BANK_SELECT_LSB`]
+    /// values sent before the program change on `channel`, each being 0 until
+    /// first sent.
+    pub fn bank_program_changes(&self, channel: Channel) -> Vec<(u64, u16, u8)> {
+        let mut bank_select_msb: u8 = 0;
+        let mut bank_select_lsb: u8 = 0;
+        let mut bank_program_changes = Vec::new();
+        for (AbsoluteTicks(tick), track_event) in self.absolute_events() {
+            let Event::Channel(channel_event) = &track_event.kind else {
+                continue;
+            };
+            if channel_event.channel != channel {
+                continue;
+            }
+            match channel_event.message {
+                ChannelMessage::ControlChange { controller, value } => match controller {
+                    Controller::BANK_SELECT_MSB => bank_select_msb = value,
+                    Controller::BANK_SELECT_LSB => bank_select_lsb = value,
+                    _ => {}
+                },
+                ChannelMessage::ProgramChange { program } => {
+                    let bank = u16::from(bank_select_msb) << 7 | u16::from(bank_select_lsb);
+                    bank_program_changes.push((tick, bank, program));
+                }
+                _ => {}
+            }
+        }
+        bank_program_changes
+    }
+
+    /// The largest number of notes sounding at once in this [`TrackChunk`].
+    ///
+    /// Notes are told apart by channel and key. A `NoteOn` with a velocity of
+    /// zero ends a note like a `NoteOff`, and a `NoteOff` with no sounding
+    /// note to end is ignored. Notes on the channels excluded by `options`
+    /// are not counted.
+    pub fn max_polyphony(&self, options: &AnalysisOptions) -> usize {
+        let mut sounding: BTreeMap<(Channel, u8), usize> = BTreeMap::new();
+        let mut polyphony: usize = 0;
+        let mut max_polyphony: usize = 0;
+        for track_event in self.iter() {
+            let Some((channel, key, is_note_on)) = note_transition(&track_event.kind) else {
+                continue;
+            };
+            if !options.includes(channel) {
+                continue;
+            }
+            let count = sounding.entry((channel, key)).or_default();
+            if is_note_on {
+                *count += 1;
+                polyphony += 1;
+                max_polyphony = max_polyphony.max(polyphony);
+            } else if *count > 0 {
+                *count -= 1;
+                polyphony -= 1;
+            }
+        }
+        max_polyphony
+    }
+
+    /// The notes of this [`TrackChunk`] that are never ended, as
+    /// `(start_tick, channel, key)` sorted by start tick, then channel and key.
+    ///
+    /// Each `NoteOff`, or `NoteOn` with a velocity of zero, ends the earliest
+    /// sounding note of the same channel and key.
+    pub fn hanging_notes(&self) -> Vec<(u64, Channel, u8)> {
+        let mut sounding: BTreeMap<(Channel, u8), VecDeque<u64>> = BTreeMap::new();
+        for (AbsoluteTicks(tick), track_event) in self.absolute_events() {
+            let Some((channel, key, is_note_on)) = note_transition(&track_event.kind) else {
+                continue;
+            };
+            let start_ticks = sounding.entry((channel, key)).or_default();
+            if is_note_on {
+                start_ticks.push_back(tick);
+            } else {
+                start_ticks.pop_front();
+            }
+        }
+
+        let mut hanging_notes: Vec<(u64, Channel, u8)> = sounding
+            .into_iter()
+            .flat_map(|((channel, key), start_ticks)| {
+                start_ticks
+                    .into_iter()
+                    .map(move |start_tick| (start_tick, channel, key))
+            })
+            .collect();
+        hanging_notes.sort_unstable();
+        hanging_notes
+    }
+
+    /// Ends every note returned by [`TrackChunk::hanging_notes`] with a
+    /// `NoteOff` of velocity zero.
+    ///
+    /// The `NoteOff`s are placed at the end of the track, just before its
+    /// [`MetaEvent::EndOfTrack`] if any, in the order of
+    /// [`TrackChunk::hanging_notes`].
+    pub fn close_hanging_notes(&mut self) {
+        let hanging_notes = self.hanging_notes();
+        if hanging_notes.is_empty() {
+            return;
+        }
+
+        let end_of_track = match self.0.last() {
+            Some(TrackEvent {
+                kind: Event::Meta(MetaEvent::EndOfTrack),
+                ..
+            }) => self.0.pop(),
+            _ => None,
+        };
+
+        // The first `NoteOff` takes the place of `EndOfTrack` in time, so the
+        // length of the track does not change.
+        let mut delta_time = end_of_track
+            .as_ref()
+            .map_or(DeltaTicks(0), |end_of_track| end_of_track.delta_time);
+        for (_, channel, key) in hanging_notes {
+            self.0.push(TrackEvent {
+                delta_time: ::core::mem::take(&mut delta_time),
+                kind: Event::Channel(ChannelEvent {
+                    channel,
+                    message: ChannelMessage::NoteOff { key, velocity: 0 },
+                }),
+            });
+        }
+
+        if let Some(end_of_track) = end_of_track {
+            self.0.push(TrackEvent {
+                delta_time: DeltaTicks(0),
+                ..end_of_track
+            });
+        }
+    }
+
+    /// The number of bytes this [`TrackChunk`] takes in a file, including its
+    /// kind and length.
+    pub fn byte_len(&self) -> usize {
+        8 + self
+            .iter()
+            .map(|track_event| {
+                variable_length_quantity_len(u64::from(track_event.delta_time.0))
+                    + track_event.kind.byte_len()
+            })
+            .sum::<usize>()
+    }
+
+    /// Whether this [`TrackChunk`] and `other` hold the same events with the
+    /// same delta-times, whether or not either ends with
+    /// [`MetaEvent::EndOfTrack`].
+    ///
+    /// Events are compared decoded, so how they were encoded in a file, such
+    /// as with or without running status, does not matter.
+    pub fn semantically_eq(&self, other: &TrackChunk) -> bool {
+        self.without_end_of_track() == other.without_end_of_track()
+    }
+
+    /// Feeds the events of this [`TrackChunk`] to `hasher` along with their
+    /// absolute time, in an encoding that ignores the same differences as
+    /// [`TrackChunk::semantically_eq`] and does not depend on the platform.
+    pub(crate) fn hash_content(&self, hasher: &mut impl Hasher) {
+        let track_events = self.without_end_of_track();
+        hasher.write(TRACK_CHUNK_KIND);
+        hasher.write(&(track_events.len() as u64).to_le_bytes());
+        let mut tick: u64 = 0;
+        for track_event in track_events {
+            tick += u64::from(track_event.delta_time.0);
+            hasher.write(&tick.to_le_bytes());
+            track_event.kind.hash_content(hasher);
+        }
+    }
+
+    /// The events of this [`TrackChunk`], except a final
+    /// [`MetaEvent::EndOfTrack`].
+    fn without_end_of_track(&self) -> &[TrackEvent] {
+        match self.split_last() {
+            Some((
+                TrackEvent {
+                    kind: Event::Meta(MetaEvent::EndOfTrack),
+                    ..
+                },
+                track_events,
+            )) => track_events,
+            _ => self,
+        }
+    }
+}
+
+/// Whether `kind` starts or ends a note, as `(channel, key, is_note_on)`.
+///
+/// A `NoteOn` with a velocity of zero ends a note.
+fn note_transition(kind: &Event) -> Option<(Channel, u8, bool)> {
+    let Event::Channel(channel_event) = kind else {
+        return None;
+    };
+    match channel_event.message {
+        ChannelMessage::NoteOn { key, velocity } => {
+            Some((channel_event.channel, key, velocity > 0))
+        }
+        ChannelMessage::NoteOff { key, .. } => Some((channel_event.channel, key, false)),
+        _ => None,
+    }
+}
+
+impl TrackEvent {
+    /// A hex dump of this [`TrackEvent`] as written to a file, for debugging:
+    /// its delta-time as a variable-length quantity on the first line, then
+    /// its event bytes as a [`pretty_hex::pretty_hex`] block.
+    ///
+    /// The bytes are re-encoded rather than kept from parsing, so the status
+    /// byte is always written, even if the file used running status. Fails
+    /// like `Vec::try_from` if this [`TrackEvent`] cannot be written.
+    pub fn hex_dump(&self) -> Result<String, EncodeError> {
+        let mut delta_time = Vec::new();
+        push_variable_length_quantity(&mut delta_time, u64::from(self.delta_time.0))
+            .ok_or(EncodeError::DeltaTimeOverflow)?;
+        let event = Vec::try_from(&self.kind).map_err(EncodeError::EventToBytes)?;
+        Ok(format!(
+            "delta_time: {}\nevent:\n{}",
+            pretty_hex::simple_hex(&delta_time),
+            pretty_hex::pretty_hex(&event)
+        ))
+    }
+}
+
+impl TryFrom<&TrackEvent> for Vec<u8> {
+    type Error = EncodeError;
+
+    /// Encodes `value` as its delta-time followed by its event. Running
+    /// status is never used.
+    fn try_from(value: &TrackEvent) -> Result<Self, Self::Error> {
+        let mut bytes = Vec::new();
+        push_variable_length_quantity(&mut bytes, u64::from(value.delta_time.0))
+            .ok_or(EncodeError::DeltaTimeOverflow)?;
+        bytes.extend(Vec::try_from(&value.kind).map_err(EncodeError::EventToBytes)?);
+        Ok(bytes)
+    }
+}
+
+impl TryFrom<&TrackChunk> for Vec<u8> {
+    type Error = EncodeError;
+
+    fn try_from(value: &TrackChunk) -> Result<Self, Self::Error> {
+        let mut events = Vec::new();
+        for track_event in value.iter() {
+            events.extend(Vec::try_from(track_event)?);
+        }
+        let length = u32::try_from(events.len()).map_err(|_| EncodeError::ChunkTooLong)?;
+        let mut bytes = Vec::with_capacity(8 + events.len());
+        bytes.extend(TRACK_CHUNK_KIND);
+        bytes.extend(length.to_be_bytes());
+        bytes.extend(events);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{
+        core::event::{channel::DRUM_CHANNEL, sysex::SysExEventKind},
+        file::chunk::{ChunkFile, track::TrackChunkFile},
+    };
+
+    /// Parses `bytes` as the events of a track chunk, following `options`.
+    fn track_chunk(bytes: &[u8], options: &ParseOptions) -> TrackChunk {
+        let chunk_file = ChunkFile {
+            kind: TRACK_CHUNK_KIND,
+            length: bytes.len() as u32,
+            offset: 0,
+            data: bytes,
+        };
+        let track_chunk_file = TrackChunkFile::try_from(&chunk_file).unwrap();
+        let track_events_file =
+            TrackEventsFile::try_from_with_options(&track_chunk_file, options).unwrap();
+        TrackChunk::try_from_with_options(&track_events_file, options).unwrap()
+    }
+
+    fn channel_event(delta_time: u32, channel: u8, message: ChannelMessage) -> TrackEvent {
+        TrackEvent {
+            delta_time: DeltaTicks(delta_time),
+            kind: Event::Channel(ChannelEvent {
+                channel: Channel::new(channel).unwrap(),
+                message,
+            }),
+        }
+    }
+
+    fn note_on(delta_time: u32, key: u8, velocity: u8) -> TrackEvent {
+        channel_event(delta_time, 0, ChannelMessage::NoteOn { key, velocity })
+    }
+
+    fn note_off(delta_time: u32, key: u8) -> TrackEvent {
+        channel_event(delta_time, 0, ChannelMessage::NoteOff { key, velocity: 0 })
+    }
+
+    fn end_of_track(delta_time: u32) -> TrackEvent {
+        TrackEvent {
+            delta_time: DeltaTicks(delta_time),
+            kind: Event::Meta(MetaEvent::EndOfTrack),
+        }
+    }
+
+    fn kinds(track_chunk: &TrackChunk) -> Vec<&Event> {
+        track_chunk
+            .iter()
+            .map(|track_event| &track_event.kind)
+            .collect()
+    }
+
+    #[test]
+    fn missing_end_of_track_is_synthesized() {
+        let options = ParseOptions {
+            synthesize_end_of_track: true,
+            ..ParseOptions::default()
+        };
+
+        let track_chunk = track_chunk(&[0x00, 0xFF, 0x01, 0x00], &options);
+
+        assert_eq!(track_chunk.len(), 2);
+        assert_eq!(track_chunk[1].delta_time, DeltaTicks(0));
+        assert_eq!(track_chunk[1].kind, Event::Meta(MetaEvent::EndOfTrack));
+    }
+
+    #[test]
+    fn present_end_of_track_is_kept_alone() {
+        let options = ParseOptions {
+            synthesize_end_of_track: true,
+            ..ParseOptions::default()
+        };
+
+        let track_chunk = track_chunk(&[0x00, 0xFF, 0x2F, 0x00], &options);
+
+        assert_eq!(
+            kinds(&track_chunk),
+            vec![&Event::Meta(MetaEvent::EndOfTrack)]
+        );
+    }
+
+    #[test]
+    fn clone_is_independent_of_original() {
+        let original = TrackChunk(vec![note_on(0, 60, 64), end_of_track(96)]);
+
+        let mut clone = original.clone();
+        clone.0[0] = note_on(0, 72, 64);
+
+        assert_eq!(original.0[0], note_on(0, 60, 64));
+        assert_ne!(clone, original);
+    }
+
+    #[test]
+    fn transpose_middle_c_up_an_octave() {
+        let mut track_chunk = TrackChunk(vec![note_on(0, 60, 64), end_of_track(96)]);
+
+        track_chunk.transpose(12, &AnalysisOptions::default());
+
+        assert_eq!(track_chunk.0, vec![note_on(0, 72, 64), end_of_track(96)]);
+    }
+
+    #[test]
+    fn quantize_snaps_to_nearest_grid_tick() {
+        let mut track_chunk = TrackChunk(vec![
+            note_on(119, 60, 64),
+            note_on(2, 64, 64),
+            end_of_track(0),
+        ]);
+
+        track_chunk.quantize(120).unwrap();
+
+        assert_eq!(
+            track_chunk.0,
+            vec![note_on(120, 60, 64), note_on(0, 64, 64), end_of_track(0)]
+        );
+    }
+
+    #[test]
+    fn quantize_rejects_delta_time_overflow() {
+        // The second event rounds up from `DeltaTicks::MAX` to 0x1000_0000.
+        let original = TrackChunk(vec![note_on(0, 60, 64), note_on(DeltaTicks::MAX.0, 64, 64)]);
+        let mut track_chunk = original.clone();
+
+        assert!(matches!(
+            track_chunk.quantize(2),
+            Err(EditError::DeltaTimeOverflow)
+        ));
+        assert_eq!(track_chunk, original);
+    }
+
+    #[test]
+    fn max_polyphony_of_three_note_chord() {
+        let track_chunk = TrackChunk(vec![
+            // A `NoteOff` with no sounding note must not underflow.
+            note_off(0, 59),
+            note_on(0, 60, 64),
+            note_on(0, 64, 64),
+            note_on(0, 67, 64),
+            note_off(96, 60),
+            note_on(0, 64, 0),
+            note_off(0, 67),
+            note_on(4, 72, 64),
+            end_of_track(0),
+        ]);
+
+        assert_eq!(track_chunk.max_polyphony(&AnalysisOptions::default()), 3);
+    }
+
+    #[test]
+    fn hanging_notes_returns_only_open_note() {
+        let track_chunk = TrackChunk(vec![
+            note_on(0, 60, 64),
+            channel_event(
+                10,
+                1,
+                ChannelMessage::NoteOn {
+                    key: 62,
+                    velocity: 64,
+                },
+            ),
+            note_off(10, 60),
+            end_of_track(30),
+        ]);
+
+        assert_eq!(
+            track_chunk.hanging_notes(),
+            vec![(10, Channel::new(1).unwrap(), 62)]
+        );
+    }
+
+    #[test]
+    fn close_hanging_notes_leaves_none_hanging() {
+        let mut track_chunk = TrackChunk(vec![
+            note_on(0, 60, 64),
+            channel_event(
+                10,
+                1,
+                ChannelMessage::NoteOn {
+                    key: 62,
+                    velocity: 64,
+                },
+            ),
+            note_on(5, 70, 64),
+            note_off(5, 60),
+            end_of_track(80),
+        ]);
+
+        track_chunk.close_hanging_notes();
+
+        assert!(track_chunk.hanging_notes().is_empty());
+        assert_eq!(track_chunk.duration_ticks(), 100);
+        assert_eq!(
+            track_chunk.0[4..],
+            [
+                channel_event(
+                    80,
+                    1,
+                    ChannelMessage::NoteOff {
+                        key: 62,
+                        velocity: 0
+                    }
+                ),
+                note_off(0, 70),
+                end_of_track(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn remap_channel_moves_note_to_drum_channel() {
+        let mut track_chunk = TrackChunk(vec![
+            TrackEvent {
+                delta_time: DeltaTicks(0),
+                kind: Event::Meta(MetaEvent::MIDIChannelPrefix(0)),
+            },
+            note_on(0, 36, 100),
+            channel_event(
+                1,
+                1,
+                ChannelMessage::NoteOn {
+                    key: 60,
+                    velocity: 100,
+                },
+            ),
+        ]);
+
+        track_chunk.remap_channel(Channel::new(0).unwrap(), DRUM_CHANNEL);
+
+        assert_eq!(
+            kinds(&track_chunk),
+            vec![
+                &Event::Meta(MetaEvent::MIDIChannelPrefix(9)),
+                &channel_event(
+                    0,
+                    9,
+                    ChannelMessage::NoteOn {
+                        key: 36,
+                        velocity: 100
+                    }
+                )
+                .kind,
+                &channel_event(
+                    1,
+                    1,
+                    ChannelMessage::NoteOn {
+                        key: 60,
+                        velocity: 100
+                    }
+                )
+                .kind,
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_to_channel_drops_other_channels() {
+        let track_chunk = TrackChunk(vec![
+            note_on(0, 60, 64),
+            channel_event(
+                10,
+                1,
+                ChannelMessage::NoteOn {
+                    key: 62,
+                    velocity: 64,
+                },
+            ),
+            note_off(10, 60),
+            end_of_track(0),
+        ]);
+
+        let filtered = track_chunk
+            .filter_to_channel(Channel::new(0).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            filtered.0,
+            vec![note_on(0, 60, 64), note_off(20, 60), end_of_track(0)]
+        );
+    }
+
+    #[test]
+    fn filter_to_channel_rejects_delta_time_overflow() {
+        let track_chunk = TrackChunk(vec![
+            note_on(0, 60, 64),
+            channel_event(
+                DeltaTicks::MAX.0,
+                1,
+                ChannelMessage::NoteOn {
+                    key: 62,
+                    velocity: 64,
+                },
+            ),
+            note_off(1, 60),
+        ]);
+
+        assert!(matches!(
+            track_chunk.filter_to_channel(Channel::new(0).unwrap()),
+            Err(EditError::DeltaTimeOverflow)
+        ));
+    }
+
+    #[test]
+    fn iterates_by_reference_and_by_value() {
+        let track_chunk = TrackChunk(vec![note_on(5, 60, 64), end_of_track(0)]);
+
+        let mut borrowed: Vec<&TrackEvent> = Vec::new();
+        for track_event in &track_chunk {
+            borrowed.push(track_event);
+        }
+        assert_eq!(borrowed, [&note_on(5, 60, 64), &end_of_track(0)]);
+
+        let mut owned: Vec<TrackEvent> = Vec::new();
+        for track_event in track_chunk {
+            owned.push(track_event);
+        }
+        assert_eq!(owned, [note_on(5, 60, 64), end_of_track(0)]);
+    }
+
+    #[test]
+    fn index_get_and_len() {
+        let track_chunk = TrackChunk(vec![note_on(5, 60, 64), end_of_track(0)]);
+
+        assert_eq!(track_chunk.len(), 2);
+        assert!(!track_chunk.is_empty());
+        assert_eq!(track_chunk[0], note_on(5, 60, 64));
+        assert_eq!(track_chunk.get(1), Some(&end_of_track(0)));
+        assert_eq!(track_chunk.get(2), None);
+        assert!(TrackChunk(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn bank_select_resolves_with_program_change() {
+        let control_change = |delta_time, channel, controller, value| {
+            channel_event(
+                delta_time,
+                channel,
+                ChannelMessage::ControlChange { controller, value },
+            )
+        };
+        let program_change = |delta_time, program| {
+            channel_event(delta_time, 0, ChannelMessage::ProgramChange { program })
+        };
+        let track_chunk = TrackChunk(vec![
+            program_change(0, 5),
+            control_change(10, 0, Controller::BANK_SELECT_MSB, 1),
+            control_change(0, 0, Controller::BANK_SELECT_LSB, 2),
+            control_change(0, 1, Controller::BANK_SELECT_MSB, 9),
+            program_change(0, 7),
+        ]);
+
+        // 1 << 7 | 2, untouched by the bank select of the second channel.
+        assert_eq!(
+            track_chunk.bank_program_changes(Channel::new(0).unwrap()),
+            vec![(0, 0, 5), (10, 130, 7)]
+        );
+    }
+
+    #[test]
+    fn channel_prefix_lasts_until_channel_event() {
+        let meta = |meta_event| TrackEvent {
+            delta_time: DeltaTicks(0),
+            kind: Event::Meta(meta_event),
+        };
+        let track_chunk = TrackChunk(vec![
+            meta(MetaEvent::MIDIPort(0)),
+            meta(MetaEvent::MIDIChannelPrefix(3)),
+            meta(MetaEvent::MIDIPort(1)),
+            channel_event(
+                1,
+                5,
+                ChannelMessage::NoteOn {
+                    key: 60,
+                    velocity: 64,
+                },
+            ),
+            meta(MetaEvent::MIDIPort(2)),
+            end_of_track(0),
+        ]);
+
+        let channels: Vec<Option<u8>> = track_chunk
+            .with_effective_channel()
+            .into_iter()
+            .map(|(channel, _)| channel.map(u8::from))
+            .collect();
+
+        assert_eq!(channels, vec![None, Some(3), Some(3), Some(5), None, None]);
+    }
+
+    #[test]
+    fn hex_dump_of_note_on() {
+        let track_event = note_on(200, 60, 64);
+
+        assert_eq!(
+            track_event.hex_dump().unwrap(),
+            "delta_time: 81 48\n\
+             event:\n\
+             Length: 3 (0x3) bytes\n\
+             0000:   90 3c 40                                             .<@"
+        );
+    }
+
+    #[test]
+    fn scale_velocity_leaves_note_off_at_zero() {
+        let mut track_chunk = TrackChunk(vec![
+            note_on(0, 60, 64),
+            note_on(1, 60, 0),
+            note_on(1, 61, 100),
+            end_of_track(0),
+        ]);
+
+        track_chunk.scale_velocity(1.5);
+
+        assert_eq!(
+            track_chunk.0,
+            [
+                note_on(0, 60, 96),
+                note_on(1, 60, 0),
+                note_on(1, 61, 127),
+                end_of_track(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn notes_only_drops_control_changes() {
+        let track_chunk = TrackChunk(vec![
+            note_on(0, 60, 64),
+            channel_event(
+                10,
+                0,
+                ChannelMessage::ControlChange {
+                    controller: Controller(7),
+                    value: 90,
+                },
+            ),
+            channel_event(5, 0, ChannelMessage::PitchBend { value: 0x2000 }),
+            note_off(5, 60),
+            end_of_track(0),
+        ]);
+
+        let notes_only = track_chunk.notes_only().unwrap();
+
+        assert!(!notes_only.iter().any(|track_event| matches!(
+            track_event.kind,
+            Event::Channel(ChannelEvent {
+                message: ChannelMessage::ControlChange { .. },
+                ..
+            })
+        )));
+        assert_eq!(
+            notes_only.0,
+            [note_on(0, 60, 64), note_off(20, 60), end_of_track(0)]
+        );
+    }
+
+    #[test]
+    fn channel_events_skips_meta_events() {
+        let track_chunk = TrackChunk(vec![
+            TrackEvent {
+                delta_time: DeltaTicks(0),
+                kind: Event::Meta(MetaEvent::SetTempo(500_000)),
+            },
+            note_on(0, 60, 90),
+            TrackEvent {
+                delta_time: DeltaTicks(96),
+                kind: Event::Meta(MetaEvent::MIDIPort(0)),
+            },
+            note_off(24, 60),
+            end_of_track(0),
+        ]);
+
+        let channel_events: Vec<_> = track_chunk
+            .channel_events()
+            .map(|(delta_time, channel_event)| (delta_time, channel_event.message.clone()))
+            .collect();
+
+        assert_eq!(
+            channel_events,
+            [
+                (
+                    DeltaTicks(0),
+                    ChannelMessage::NoteOn {
+                        key: 60,
+                        velocity: 90
+                    }
+                ),
+                (
+                    DeltaTicks(24),
+                    ChannelMessage::NoteOff {
+                        key: 60,
+                        velocity: 0
+                    }
+                ),
+            ]
+        );
+    }
+
+    /// The kinds of the system exclusive events of the track chunk `bytes`.
+    fn sys_ex_kinds(bytes: &[u8]) -> Vec<SysExEventKind> {
+        track_chunk(bytes, &ParseOptions::default())
+            .iter()
+            .filter_map(|track_event| match &track_event.kind {
+                Event::SysEx(sys_ex_event) => Some(sys_ex_event.kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn isolated_f7_is_an_escape() {
+        assert_eq!(
+            sys_ex_kinds(&[0x00, 0xF7, 0x01, 0xF8, 0x00, 0xFF, 0x2F, 0x00]),
+            [SysExEventKind::Escape]
+        );
+    }
+
+    #[test]
+    fn f7_after_unfinished_message_is_a_continuation() {
+        assert_eq!(
+            sys_ex_kinds(&[
+                0x00, 0xF0, 0x03, 0x43, 0x12, 0x00, 0x81, 0x48, 0xF7, 0x04, 0x43, 0x12, 0x00, 0xF7,
+                0x00, 0xF7, 0x01, 0xFA, 0x00, 0xFF, 0x2F, 0x00,
+            ]),
+            [
+                SysExEventKind::Normal,
+                SysExEventKind::Continuation,
+                SysExEventKind::Escape,
+            ]
+        );
+    }
+
+    #[test]
+    fn encoding_rejects_delta_time_above_max() {
+        let track_chunk = TrackChunk(vec![end_of_track(u32::MAX)]);
+
+        assert!(matches!(
+            Vec::try_from(&track_chunk),
+            Err(EncodeError::DeltaTimeOverflow)
+        ));
+        assert!(matches!(
+            track_chunk[0].hex_dump(),
+            Err(EncodeError::DeltaTimeOverflow)
+        ));
+    }
+
+    #[test]
+    fn encoding_accepts_delta_time_max() {
+        let track_chunk = TrackChunk(vec![end_of_track(DeltaTicks::MAX.0)]);
+
+        assert_eq!(
+            Vec::try_from(&track_chunk).unwrap(),
+            b"MTrk\0\0\0\x07\xFF\xFF\xFF\x7F\xFF\x2F\x00"
+        );
+    }
 }
@@ -0,0 +1,461 @@
+use alloc::vec::Vec;
+
+use derive_more::{Debug, Display, Error, Into};
+
+use crate::{
+    core::event::controller::Controller,
+    file::event::track::{
+        MIDIEventFile, TRACK_EVENT_STATUS_80_MIN_MIDI, TRACK_EVENT_STATUS_EF_MAX_MIDI,
+    },
+};
+
+/// The number of data bytes following a channel voice message `status`,
+/// which only depends on its high nibble:
+///
+/// | Status | Message                 | Data bytes |
+/// | ------ | ----------------------- | ---------- |
+/// | `8n`   | Note Off                | 2          |
+/// | `9n`   | Note On                 | 2          |
+/// | `An`   | Polyphonic Key Pressure | 2          |
+/// | `Bn`   | Control Change          | 2          |
+/// | `Cn`   | Program Change          | 1          |
+/// | `Dn`   | Channel Pressure        | 1          |
+/// | `En`   | Pitch Bend              | 2          |
+///
+/// Any other `status` is not a channel voice message and has no data bytes
+/// by this table.
+pub const fn channel_message_data_len(status: u8) -> usize {
+    match status & 0xF0 {
+        0x80 | 0x90 | 0xA0 | 0xB0 | 0xE0 => 2,
+        0xC0 | 0xD0 => 1,
+        _ => 0,
+    }
+}
+
+/// The channel (10, or 9 when counting from 0) reserved for percussion by
+/// General MIDI, on which keys select drum sounds rather than pitches.
+pub const DRUM_CHANNEL: Channel = Channel(9);
+
+/// One of the 16 MIDI channels, counted from 0 as in the low nibble of a
+/// status byte.
+///
+/// MIDI channels are usually numbered from 1 to 16 for users, see
+/// [`Channel::display_number`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Into)]
+pub struct Channel(u8);
+
+impl Channel {
+    /// Creates the [`Channel`] of `index` in `0..=15`, or `None` if `index`
+    /// is out of range.
+    pub const fn new(index: u8) -> Option<Self> {
+        if index > 0x0F {
+            return None;
+        }
+        Some(Channel(index))
+    }
+
+    /// The [`Channel`] addressed by the low nibble of `status`.
+    pub(crate) const fn from_status(status: u8) -> Self {
+        Channel(status & 0x0F)
+    }
+
+    /// The number of this [`Channel`] as shown to users, from 1 to 16.
+    pub const fn display_number(&self) -> u8 {
+        self.0 + 1
+    }
+}
+
+/// A set of [`Channel`]s, stored as one bit per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[debug("ChannelSet({:#06X})", _0)]
+pub struct ChannelSet(u16);
+
+impl ChannelSet {
+    /// The set of no channel.
+    pub const EMPTY: ChannelSet = ChannelSet(0);
+
+    /// The set of all 16 channels.
+    pub const ALL: ChannelSet = ChannelSet(u16::MAX);
+
+    /// This [`ChannelSet`] with `channel` added, usable in constants.
+    pub const fn with(self, channel: Channel) -> Self {
+        ChannelSet(self.0 | 1 << channel.0)
+    }
+
+    /// Adds `channel` to this [`ChannelSet`].
+    pub fn insert(&mut self, channel: Channel) {
+        *self = self.with(channel);
+    }
+
+    /// Removes `channel` from this [`ChannelSet`].
+    pub fn remove(&mut self, channel: Channel) {
+        self.0 &= !(1 << channel.0);
+    }
+
+    /// Whether `channel` is in this [`ChannelSet`].
+    pub const fn contains(&self, channel: Channel) -> bool {
+        self.0 & 1 << channel.0 != 0
+    }
+
+    /// Whether this [`ChannelSet`] has no channel.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl FromIterator<Channel> for ChannelSet {
+    fn from_iter<T: IntoIterator<Item = Channel>>(iter: T) -> Self {
+        iter.into_iter().fold(ChannelSet::EMPTY, ChannelSet::with)
+    }
+}
+
+/// A channel voice message, decoded from its status byte and data bytes.
+///
+/// The status byte's high nibble selects the [`ChannelMessage`] and its low
+/// nibble addresses one of the 16 MIDI channels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelEvent {
+    /// The MIDI channel this event is sent on.
+    pub channel: Channel,
+
+    pub message: ChannelMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelMessage {
+    /// `8n kk vv`: The key `kk` has been released with velocity `vv`.
+    NoteOff { key: u8, velocity: u8 },
+
+    /// `9n kk vv`: The key `kk` has been pressed with velocity `vv`. A
+    /// velocity of zero is conventionally treated as a note off.
+    NoteOn { key: u8, velocity: u8 },
+
+    /// `An kk vv`: The pressure applied to the already pressed key `kk` has
+    /// changed to `vv`.
+    PolyphonicKeyPressure { key: u8, pressure: u8 },
+
+    /// `Bn cc vv`: The controller `cc` has changed to `vv`.
+    ControlChange { controller: Controller, value: u8 },
+
+    /// `Cn pp`: The patch number has changed to `pp`.
+    ProgramChange { program: u8 },
+
+    /// `Dn vv`: The greatest pressure applied to any of the pressed keys has
+    /// changed to `vv`.
+    ChannelPressure { pressure: u8 },
+
+    /// `En ll mm`: The pitch wheel has changed. `value` is the 14-bit
+    /// combination of `ll` (least significant) and `mm` (most significant),
+    /// where `0x2000` means no bend.
+    PitchBend { value: u16 },
+}
+
+/// The kind of message a status byte starts, from its high nibble alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    NoteOff,
+    NoteOn,
+    PolyphonicKeyPressure,
+    ControlChange,
+    ProgramChange,
+    ChannelPressure,
+    PitchBend,
+
+    /// `F0`-`FF`: A System Exclusive, System Common or System Real-Time
+    /// message, or a meta event in a file. Not addressed to a channel.
+    System,
+
+    /// `00`-`7F`: A data byte, which only appears as a status under running
+    /// status.
+    Data,
+}
+
+/// The [`MessageType`] of `status`, without decoding the rest of the message.
+pub const fn message_type(status: u8) -> MessageType {
+    match status >> 4 {
+        0x8 => MessageType::NoteOff,
+        0x9 => MessageType::NoteOn,
+        0xA => MessageType::PolyphonicKeyPressure,
+        0xB => MessageType::ControlChange,
+        0xC => MessageType::ProgramChange,
+        0xD => MessageType::ChannelPressure,
+        0xE => MessageType::PitchBend,
+        0xF => MessageType::System,
+        _ => MessageType::Data,
+    }
+}
+
+/// The [`Channel`] addressed by `status`, or `None` if it is not the status of
+/// a channel voice message.
+pub const fn channel(status: u8) -> Option<Channel> {
+    match status {
+        TRACK_EVENT_STATUS_80_MIN_MIDI..=TRACK_EVENT_STATUS_EF_MAX_MIDI => {
+            Some(Channel::from_status(status))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Display, Error)]
+pub enum TryFromError {
+    /// The message does not start with a status byte, as happens under
+    /// running status.
+    MissingStatus,
+    #[debug("InvalidStatus({:X})", _0)]
+    InvalidStatus(#[error(ignore)] u8),
+    InvalidDataLength,
+    InvalidData,
+}
+
+impl ChannelEvent {
+    /// Decodes a [`ChannelEvent`] from a `status` byte and exactly as many
+    /// `data` bytes as that status requires.
+    pub(crate) fn from_parts(status: u8, data: &[u8]) -> Result<Self, TryFromError> {
+        if !(TRACK_EVENT_STATUS_80_MIN_MIDI..=TRACK_EVENT_STATUS_EF_MAX_MIDI).contains(&status) {
+            return Err(TryFromError::InvalidStatus(status));
+        }
+        if data.len() != channel_message_data_len(status) {
+            return Err(TryFromError::InvalidDataLength);
+        }
+        if data.iter().any(|byte| byte & 0x80 != 0) {
+            return Err(TryFromError::InvalidData);
+        }
+
+        let channel = Channel::from_status(status);
+        let message = match (status & 0xF0, data) {
+            (0x80, &[key, velocity]) => ChannelMessage::NoteOff { key, velocity },
+            (0x90, &[key, velocity]) => ChannelMessage::NoteOn { key, velocity },
+            (0xA0, &[key, pressure]) => ChannelMessage::PolyphonicKeyPressure { key, pressure },
+            (0xB0, &[controller, value]) => ChannelMessage::ControlChange {
+                controller: Controller(controller),
+                value,
+            },
+            (0xC0, &[program]) => ChannelMessage::ProgramChange { program },
+            (0xD0, &[pressure]) => ChannelMessage::ChannelPressure { pressure },
+            (0xE0, &[lsb, msb]) => ChannelMessage::PitchBend {
+                value: u16::from(msb) << 7 | u16::from(lsb),
+            },
+            _ => return Err(TryFromError::InvalidStatus(status)),
+        };
+
+        Ok(ChannelEvent { channel, message })
+    }
+}
+
+impl<'a> TryFrom<&'a MIDIEventFile<'a>> for ChannelEvent {
+    type Error = TryFromError;
+
+    fn try_from(value: &MIDIEventFile) -> Result<Self, Self::Error> {
+        ChannelEvent::from_parts(*value.status, value.data)
+    }
+}
+
+impl TryFrom<&[u8]> for ChannelEvent {
+    type Error = TryFromError;
+
+    /// Decodes a [`ChannelEvent`] from a complete live MIDI message: its
+    /// status byte followed by exactly as many data bytes as that status
+    /// requires, the inverse of `ChannelEvent::to_wire_bytes`.
+    ///
+    /// Running status is not supported, so `value` must start with a status
+    /// byte.
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value.split_first() {
+            Some((status, data)) if status & 0x80 != 0 => ChannelEvent::from_parts(*status, data),
+            _ => Err(TryFromError::MissingStatus),
+        }
+    }
+}
+
+impl ChannelEvent {
+    /// Encodes this [`ChannelEvent`] as its status byte followed by its data
+    /// bytes, returned along with how many of the 3 bytes are used.
+    pub(crate) fn encode(&self) -> ([u8; 3], usize) {
+        let channel = self.channel.0;
+        match self.message {
+            ChannelMessage::NoteOff { key, velocity } => ([0x80 | channel, key, velocity], 3),
+            ChannelMessage::NoteOn { key, velocity } => ([0x90 | channel, key, velocity], 3),
+            ChannelMessage::PolyphonicKeyPressure { key, pressure } => {
+                ([0xA0 | channel, key, pressure], 3)
+            }
+            ChannelMessage::ControlChange { controller, value } => {
+                ([0xB0 | channel, controller.0, value], 3)
+            }
+            ChannelMessage::ProgramChange { program } => ([0xC0 | channel, program, 0], 2),
+            ChannelMessage::ChannelPressure { pressure } => ([0xD0 | channel, pressure, 0], 2),
+            ChannelMessage::PitchBend { value } => (
+                [
+                    0xE0 | channel,
+                    (value & 0x7F) as u8,
+                    ((value >> 7) & 0x7F) as u8,
+                ],
+                3,
+            ),
+        }
+    }
+}
+
+impl From<&ChannelEvent> for Vec<u8> {
+    /// Encodes `value` as its status byte followed by its data bytes, the
+    /// inverse of `ChannelEvent::try_from`.
+    fn from(value: &ChannelEvent) -> Self {
+        let (bytes, len) = value.encode();
+        bytes[..len].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_message_data_len_of_each_high_nibble() {
+        for (high_nibble, data_len) in [
+            (0x80, 2),
+            (0x90, 2),
+            (0xA0, 2),
+            (0xB0, 2),
+            (0xC0, 1),
+            (0xD0, 1),
+            (0xE0, 2),
+        ] {
+            for channel in 0x00..=0x0F {
+                assert_eq!(channel_message_data_len(high_nibble | channel), data_len);
+            }
+        }
+    }
+
+    #[test]
+    fn channel_message_data_len_of_system_status_and_data_bytes_is_zero() {
+        for status in 0xF0..=0xFF {
+            assert_eq!(channel_message_data_len(status), 0);
+        }
+        for data_byte in 0x00..=0x7F {
+            assert_eq!(channel_message_data_len(data_byte), 0);
+        }
+    }
+
+    #[test]
+    fn channel_is_zero_to_fifteen() {
+        assert_eq!(Channel::new(16), None);
+        assert_eq!(Channel::new(u8::MAX), None);
+
+        let last = Channel::new(15).unwrap();
+        assert_eq!(u8::from(last), 15);
+        assert_eq!(last.display_number(), 16);
+        assert_eq!(Channel::new(0).unwrap().display_number(), 1);
+    }
+
+    #[test]
+    fn try_from_canonical_bytes_of_each_message() {
+        let channel_event = |channel, message| ChannelEvent {
+            channel: Channel::new(channel).unwrap(),
+            message,
+        };
+        for (bytes, expected) in [
+            (
+                &[0x80, 60, 64][..],
+                channel_event(
+                    0,
+                    ChannelMessage::NoteOff {
+                        key: 60,
+                        velocity: 64,
+                    },
+                ),
+            ),
+            (
+                &[0x91, 60, 100],
+                channel_event(
+                    1,
+                    ChannelMessage::NoteOn {
+                        key: 60,
+                        velocity: 100,
+                    },
+                ),
+            ),
+            (
+                &[0xA2, 61, 5],
+                channel_event(
+                    2,
+                    ChannelMessage::PolyphonicKeyPressure {
+                        key: 61,
+                        pressure: 5,
+                    },
+                ),
+            ),
+            (
+                &[0xB3, 7, 99],
+                channel_event(
+                    3,
+                    ChannelMessage::ControlChange {
+                        controller: Controller(7),
+                        value: 99,
+                    },
+                ),
+            ),
+            (
+                &[0xC4, 12],
+                channel_event(4, ChannelMessage::ProgramChange { program: 12 }),
+            ),
+            (
+                &[0xD5, 33],
+                channel_event(5, ChannelMessage::ChannelPressure { pressure: 33 }),
+            ),
+            (
+                &[0xEF, 0x00, 0x40],
+                channel_event(15, ChannelMessage::PitchBend { value: 0x2000 }),
+            ),
+        ] {
+            assert_eq!(ChannelEvent::try_from(bytes).unwrap(), expected);
+            assert_eq!(Vec::from(&expected), bytes);
+        }
+    }
+
+    #[test]
+    fn try_from_rejects_incomplete_bytes() {
+        assert!(matches!(
+            ChannelEvent::try_from(&[60, 64][..]),
+            Err(TryFromError::MissingStatus)
+        ));
+        assert!(matches!(
+            ChannelEvent::try_from(&[][..]),
+            Err(TryFromError::MissingStatus)
+        ));
+        assert!(matches!(
+            ChannelEvent::try_from(&[0x90, 60][..]),
+            Err(TryFromError::InvalidDataLength)
+        ));
+        assert!(matches!(
+            ChannelEvent::try_from(&[0xF8][..]),
+            Err(TryFromError::InvalidStatus(0xF8))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn value_types_are_hash_map_keys() {
+        use std::collections::HashMap;
+
+        use crate::core::{
+            chunk::header::{division::fps::Fps, format::Format},
+            event::sysex::SysExEventKind,
+        };
+
+        let mut note_counts: HashMap<(Channel, u8), u32> = HashMap::new();
+        for _ in 0..2 {
+            *note_counts
+                .entry((Channel::new(1).unwrap(), 60))
+                .or_default() += 1;
+        }
+        let formats = HashMap::from([(Format::SimultaneousTracks, 1)]);
+        let fps = HashMap::from([(Fps::FPS25, 1)]);
+        let controllers = HashMap::from([(Controller::BANK_SELECT_MSB, 1)]);
+        let sys_ex_kinds = HashMap::from([(SysExEventKind::Normal, 1)]);
+
+        assert_eq!(note_counts[&(Channel::new(1).unwrap(), 60)], 2);
+        assert_eq!(formats[&Format::SimultaneousTracks], 1);
+        assert_eq!(fps[&Fps::FPS25], 1);
+        assert_eq!(controllers[&Controller::BANK_SELECT_MSB], 1);
+        assert_eq!(sys_ex_kinds[&SysExEventKind::Normal], 1);
+    }
+}
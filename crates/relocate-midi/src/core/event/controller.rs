@@ -0,0 +1,36 @@
+use derive_more::{Debug, From, Into};
+
+/// The controller number `cc` of a `Bn cc vv` control change message.
+///
+/// Any value in `0..=127` is a valid controller, so the well-known ones are
+/// provided as associated constants rather than an exhaustive enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, From, Into)]
+pub struct Controller(pub u8);
+
+impl Controller {
+    /// Selects the bank of the next program change, most significant 7 bits.
+    pub const BANK_SELECT_MSB: Controller = Controller(0x00);
+    pub const MODULATION_WHEEL: Controller = Controller(0x01);
+    /// The value of the selected RPN or NRPN, most significant 7 bits.
+    pub const DATA_ENTRY_MSB: Controller = Controller(0x06);
+    pub const CHANNEL_VOLUME: Controller = Controller(0x07);
+    pub const PAN: Controller = Controller(0x0A);
+    pub const EXPRESSION: Controller = Controller(0x0B);
+    /// Selects the bank of the next program change, least significant 7
+    /// bits.
+    pub const BANK_SELECT_LSB: Controller = Controller(0x20);
+    /// The value of the selected RPN or NRPN, least significant 7 bits.
+    pub const DATA_ENTRY_LSB: Controller = Controller(0x26);
+    pub const SUSTAIN_PEDAL: Controller = Controller(0x40);
+    /// Selects a Non-Registered Parameter Number, least significant 7 bits.
+    pub const NRPN_LSB: Controller = Controller(0x62);
+    /// Selects a Non-Registered Parameter Number, most significant 7 bits.
+    pub const NRPN_MSB: Controller = Controller(0x63);
+    /// Selects a Registered Parameter Number, least significant 7 bits.
+    pub const RPN_LSB: Controller = Controller(0x64);
+    /// Selects a Registered Parameter Number, most significant 7 bits.
+    pub const RPN_MSB: Controller = Controller(0x65);
+    pub const ALL_SOUND_OFF: Controller = Controller(0x78);
+    pub const RESET_ALL_CONTROLLERS: Controller = Controller(0x79);
+    pub const ALL_NOTES_OFF: Controller = Controller(0x7B);
+}
@@ -1,6 +1,14 @@
+use alloc::{string::String, vec, vec::Vec};
+
 use derive_more::{Debug, Display, Error};
 
-use crate::{file::event::track::MetaEventFile, scanner::Scanner};
+use crate::{
+    core::chunk::header::division::fps::Fps,
+    file::event::track::{MetaEventFile, TRACK_EVENT_STATUS_FF_META},
+    options::ParseOptions,
+    scanner::Scanner,
+    writer::push_variable_length_quantity,
+};
 
 /// In the syntax descriptions for each of the meta-events a set of conventions
 /// is used to describe parameters of the events. The FF which begins each
@@ -21,7 +29,7 @@ use crate::{file::event::track::MetaEventFile, scanner::Scanner};
 /// possible in the file, so it will be noticed easily. Sequence Number and
 /// Sequence/Track Name events, if present, must appear at time 0. An
 /// end-of-track event must occur as the last event in the track.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MetaEvent {
     /// This optional event, which must occur at the beginning of a track,
     /// before any nonzero delta-times, and before any transmittable MIDI
@@ -47,7 +55,7 @@ pub enum MetaEvent {
     /// between different programs on the same computer which supports
     /// an extended character set. Programs on a computer which does not
     /// support non-ASCII characters should ignore those characters.
-    TextEvent(String),
+    TextEvent(TextMeta),
 
     /// Contains a copyright notice as printable ASCII text. The notice should
     /// contain the characters (C), the year of the copyright, and the owner
@@ -55,31 +63,41 @@ pub enum MetaEvent {
     /// file, all of the copyright notices should be placed together in this
     /// event so that it will be at the beginning of the file. This event
     /// should be the first event in the first track chunk, at time 0.
-    CopyrightNotice(String),
+    CopyrightNotice(TextMeta),
 
     /// If in a format 0 track, or the first track in a format 1 file, the name
     /// of the sequence. Otherwise, the name of the track.
-    SequenceOrTrackName(String),
+    SequenceOrTrackName(TextMeta),
 
     /// A description of the type of instrumentation to be used in that track.
     /// May be used with the MIDI Prefix meta-event to specify which MIDI
     /// channel the description applies to, or the channel may be specified as
     /// text in the event itself.
-    InstrumentName(String),
+    InstrumentName(TextMeta),
 
     /// A lyric to be sung. Generally, each syllable will be a separate lyric
     /// event which begins at the event's time.
-    Lyric(String),
+    Lyric(TextMeta),
 
     /// Normally in a format 0 track, or the first track in a format 1 file. The
     /// name of that point in the sequence, such as a rehearsal letter or
     /// section name ("First Verse", etc.).
-    Marker(String),
+    Marker(TextMeta),
 
     /// A description of something happening on a film or video screen or stage
     /// at that point in the musical score ("Car crashes into house", "curtain
     /// opens", "she slaps his face", etc.)
-    CuePoint(String),
+    CuePoint(TextMeta),
+
+    /// Meta-event types 08 thru 0F are reserved for other text-like events,
+    /// and their text is decoded like that of [`MetaEvent::TextEvent`]. Later
+    /// recommended practice assigns 08 to the program name and 09 to the
+    /// device name.
+    #[debug("ReservedText({:X}, {:?})", kind, text)]
+    ReservedText {
+        kind: u8,
+        text: TextMeta,
+    },
 
     /// The MIDI channel (0-15) contained in this event may be used to associate
     /// a MIDI channel with all events which follow, including System Exclusive
@@ -132,13 +150,7 @@ pub enum MetaEvent {
     // Offset must be stored with the tempo map, and has no meaning in any of the other tracks.
     // The ff field contains fractional frames, in 100ths of a frame, even in SMPTE
     // based tracks which specify a different frame subdivision for delta-times.
-    SMPTEOffset {
-        hours: u8,
-        minutes: u8,
-        seconds: u8,
-        frames: u8,
-        fractional_frames: u8,
-    },
+    SMPTEOffset(SMPTEOffset),
 
     /// The time signature is expressed as four numbers.
     ///
@@ -177,6 +189,117 @@ pub enum MetaEvent {
         sharps_flats: i8,
         major_minor: u8,
     },
+
+    /// Special requirements for particular sequencers may use this event type:
+    /// the first byte or bytes of data is a manufacturer ID (these are one
+    /// byte, or, if the first byte is 00, three bytes). As with MIDI System
+    /// Exclusive, manufacturers who define something using this meta-event
+    /// should publish it so that others may be used by a sequencer which
+    /// elects to use this as its only file format.
+    SequencerSpecific(Vec<u8>),
+
+    /// A meta-event of a type this crate does not recognize. As required by
+    /// the specification, it is kept as is rather than rejected, so that it
+    /// can be written back unchanged.
+    #[debug("Unknown({:X}, {:?})", status, data)]
+    Unknown {
+        status: u8,
+        data: Vec<u8>,
+    },
+}
+
+/// The text carried by [`MetaEvent::TextEvent`] and the other text-like
+/// meta-events.
+///
+/// The original bytes are kept next to the decoded text, so that text which
+/// did not decode cleanly can still be written back byte for byte.
+#[derive(Debug, Display, Clone, PartialEq, Eq)]
+#[debug("{text:?}")]
+#[display("{text}")]
+pub struct TextMeta {
+    /// The bytes as stored in the file.
+    pub raw: Vec<u8>,
+
+    /// The decoded text, which may have lost information from `raw`.
+    pub text: String,
+}
+
+impl TextMeta {
+    /// The decoded text.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+/// The SMPTE time carried by [`MetaEvent::SMPTEOffset`], as stored in the
+/// file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SMPTEOffset {
+    /// The hour in bits 4 thru 0, and the frame rate in bits 6 and 5, just as
+    /// in MIDI Time Code.
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    /// Fractional frames, in 100ths of a frame.
+    pub fractional_frames: u8,
+}
+
+#[derive(Debug, Display, Error)]
+pub enum SMPTEOffsetError {
+    InvalidHours,
+    InvalidMinutes,
+    InvalidSeconds,
+    InvalidFrames,
+    InvalidFractionalFrames,
+}
+
+impl SMPTEOffset {
+    /// The frame rate encoded in bits 6 and 5 of the hours byte.
+    pub fn frame_rate(&self) -> Fps {
+        match (self.hours >> 5) & 0b11 {
+            0 => Fps::FPS24,
+            1 => Fps::FPS25,
+            2 => Fps::FPS30Drop,
+            _ => Fps::FPS30,
+        }
+    }
+
+    /// The hour, without the frame rate bits.
+    pub fn hour(&self) -> u8 {
+        self.hours & 0x1F
+    }
+
+    /// Checks that every field is within its range, reporting the first one
+    /// that is not.
+    ///
+    /// Frames must be below the nominal rate of [`SMPTEOffset::frame_rate`],
+    /// that is, 30 for both 30 fps rates.
+    pub fn validate(&self) -> Result<(), SMPTEOffsetError> {
+        let frames_per_second = match self.frame_rate() {
+            Fps::FPS24 => 24,
+            Fps::FPS25 => 25,
+            Fps::FPS30Drop | Fps::FPS30 => 30,
+        };
+
+        if self.hours & 0x80 != 0 || self.hour() >= 24 {
+            return Err(SMPTEOffsetError::InvalidHours);
+        }
+        if self.minutes >= 60 {
+            return Err(SMPTEOffsetError::InvalidMinutes);
+        }
+        if self.seconds >= 60 {
+            return Err(SMPTEOffsetError::InvalidSeconds);
+        }
+        if self.frames >= frames_per_second {
+            return Err(SMPTEOffsetError::InvalidFrames);
+        }
+        if self.fractional_frames >= 100 {
+            return Err(SMPTEOffsetError::InvalidFractionalFrames);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Display, Error)]
@@ -185,19 +308,62 @@ pub enum TryFromError {
     InvalidNumber,
     InvalidData,
     InvalidScannerState,
-    #[debug("InvalidStatus({:X})", _0)]
-    InvalidStatus(#[error(ignore)] u8),
+}
+
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    /// The data is longer than `0x0FFFFFFF` bytes, the largest length a
+    /// variable-length quantity can hold.
+    DataTooLong,
 }
 
 impl<'a> TryFrom<&'a MetaEventFile<'a>> for MetaEvent {
     type Error = TryFromError;
 
+    /// Text is decoded as UTF-8, replacing invalid sequences with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// A meta-event of an unrecognized type becomes [`MetaEvent::Unknown`], so
+    /// an error always means the data of a known type is malformed, such as
+    /// a [`MetaEvent::SetTempo`] that is not 3 bytes long.
     fn try_from(value: &MetaEventFile) -> Result<Self, Self::Error> {
+        MetaEvent::try_from_with_options(value, &ParseOptions::default())
+    }
+}
+
+impl MetaEvent {
+    /// Converts a [`MetaEventFile`] like `MetaEvent::try_from`, but decodes
+    /// text following `options`.
+    pub fn try_from_with_options(
+        value: &MetaEventFile,
+        options: &ParseOptions,
+    ) -> Result<Self, TryFromError> {
+        MetaEvent::try_from_with_decoder(value, |data| options.decode_text(data))
+    }
+
+    /// Converts a [`MetaEventFile`] like `MetaEvent::try_from`, but decodes
+    /// text with `encoding`, such as [`encoding_rs::SHIFT_JIS`] for many
+    /// Japanese karaoke files.
+    #[cfg(feature = "encoding")]
+    pub fn try_from_with_encoding(
+        value: &MetaEventFile,
+        encoding: &'static encoding_rs::Encoding,
+    ) -> Result<Self, TryFromError> {
+        MetaEvent::try_from_with_decoder(value, |data| {
+            encoding.decode_without_bom_handling(data).0.into_owned()
+        })
+    }
+
+    fn try_from_with_decoder(
+        value: &MetaEventFile,
+        decode: impl Fn(&[u8]) -> String,
+    ) -> Result<Self, TryFromError> {
         macro_rules! text_event {
             ($variant:ident) => {
-                Ok(MetaEvent::$variant(
-                    String::from_utf8_lossy(value.data).to_string(),
-                ))
+                Ok(MetaEvent::$variant(TextMeta {
+                    raw: value.data.to_vec(),
+                    text: decode(value.data),
+                }))
             };
         }
 
@@ -211,13 +377,20 @@ impl<'a> TryFrom<&'a MetaEventFile<'a>> for MetaEvent {
                 Ok(MetaEvent::SequenceNumber(number))
             }
 
-            0x01 | 0x08..0x10 => text_event!(TextEvent),
+            0x01 => text_event!(TextEvent),
             0x02 => text_event!(CopyrightNotice),
             0x03 => text_event!(SequenceOrTrackName),
             0x04 => text_event!(InstrumentName),
             0x05 => text_event!(Lyric),
             0x06 => text_event!(Marker),
             0x07 => text_event!(CuePoint),
+            0x08..=0x0F => Ok(MetaEvent::ReservedText {
+                kind: *value.kind,
+                text: TextMeta {
+                    raw: value.data.to_vec(),
+                    text: decode(value.data),
+                },
+            }),
 
             0x20 => {
                 let mut scanner = Scanner::new(value.data);
@@ -237,7 +410,12 @@ impl<'a> TryFrom<&'a MetaEventFile<'a>> for MetaEvent {
                 Ok(MetaEvent::MIDIPort(port))
             }
 
-            0x2F => Ok(MetaEvent::EndOfTrack),
+            0x2F => {
+                if !value.data.is_empty() {
+                    return Err(TryFromError::InvalidScannerState);
+                }
+                Ok(MetaEvent::EndOfTrack)
+            }
 
             0x51 => {
                 let mut scanner = Scanner::new(value.data);
@@ -256,13 +434,13 @@ impl<'a> TryFrom<&'a MetaEventFile<'a>> for MetaEvent {
                 if !scanner.done() {
                     return Err(TryFromError::InvalidScannerState);
                 }
-                Ok(MetaEvent::SMPTEOffset {
+                Ok(MetaEvent::SMPTEOffset(SMPTEOffset {
                     hours,
                     minutes,
                     seconds,
                     frames,
                     fractional_frames,
-                })
+                }))
             }
 
             0x58 => {
@@ -282,7 +460,7 @@ impl<'a> TryFrom<&'a MetaEventFile<'a>> for MetaEvent {
 
             0x59 => {
                 let mut scanner = Scanner::new(value.data);
-                let sharps_flats = *scanner.eat().ok_or(TryFromError::InvalidData)? as i8;
+                let sharps_flats = scanner.eat_i8().ok_or(TryFromError::InvalidData)?;
                 let major_minor = *scanner.eat().ok_or(TryFromError::InvalidData)?;
                 if !scanner.done() {
                     return Err(TryFromError::InvalidScannerState);
@@ -293,7 +471,206 @@ impl<'a> TryFrom<&'a MetaEventFile<'a>> for MetaEvent {
                 })
             }
 
-            status => Err(TryFromError::InvalidStatus(*status)),
+            0x7F => Ok(MetaEvent::SequencerSpecific(value.data.to_vec())),
+
+            status => Ok(MetaEvent::Unknown {
+                status: *status,
+                data: value.data.to_vec(),
+            }),
+        }
+    }
+}
+
+impl MetaEvent {
+    /// The text of this [`MetaEvent`], if it is one of the text-like
+    /// meta-events, from [`MetaEvent::TextEvent`] to
+    /// [`MetaEvent::ReservedText`].
+    pub fn text(&self) -> Option<&TextMeta> {
+        match self {
+            MetaEvent::TextEvent(text)
+            | MetaEvent::CopyrightNotice(text)
+            | MetaEvent::SequenceOrTrackName(text)
+            | MetaEvent::InstrumentName(text)
+            | MetaEvent::Lyric(text)
+            | MetaEvent::Marker(text)
+            | MetaEvent::CuePoint(text)
+            | MetaEvent::ReservedText { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The type byte of this [`MetaEvent`] along with its data bytes, as
+    /// written after `FF`.
+    pub(crate) fn encode(&self) -> (u8, Vec<u8>) {
+        match self {
+            MetaEvent::SequenceNumber(number) => (0x00, number.to_be_bytes().to_vec()),
+            MetaEvent::TextEvent(text) => (0x01, text.raw.clone()),
+            MetaEvent::CopyrightNotice(text) => (0x02, text.raw.clone()),
+            MetaEvent::SequenceOrTrackName(text) => (0x03, text.raw.clone()),
+            MetaEvent::InstrumentName(text) => (0x04, text.raw.clone()),
+            MetaEvent::Lyric(text) => (0x05, text.raw.clone()),
+            MetaEvent::Marker(text) => (0x06, text.raw.clone()),
+            MetaEvent::CuePoint(text) => (0x07, text.raw.clone()),
+            MetaEvent::ReservedText { kind, text } => (*kind, text.raw.clone()),
+            MetaEvent::MIDIChannelPrefix(channel) => (0x20, vec![*channel]),
+            MetaEvent::MIDIPort(port) => (0x21, vec![*port]),
+            MetaEvent::EndOfTrack => (0x2F, Vec::new()),
+            MetaEvent::SetTempo(tempo) => (0x51, tempo.to_be_bytes()[1..].to_vec()),
+            MetaEvent::SMPTEOffset(smpte_offset) => (
+                0x54,
+                vec![
+                    smpte_offset.hours,
+                    smpte_offset.minutes,
+                    smpte_offset.seconds,
+                    smpte_offset.frames,
+                    smpte_offset.fractional_frames,
+                ],
+            ),
+            MetaEvent::TimeSignature {
+                numerator,
+                denominator,
+                midi_clocks_per_metronome_click,
+                thirty_second_notes_per_midi_quarter_note,
+            } => (
+                0x58,
+                vec![
+                    *numerator,
+                    *denominator,
+                    *midi_clocks_per_metronome_click,
+                    *thirty_second_notes_per_midi_quarter_note,
+                ],
+            ),
+            MetaEvent::KeySignature {
+                sharps_flats,
+                major_minor,
+            } => (0x59, vec![*sharps_flats as u8, *major_minor]),
+            MetaEvent::SequencerSpecific(data) => (0x7F, data.clone()),
+            MetaEvent::Unknown { status, data } => (*status, data.clone()),
         }
     }
 }
+
+impl TryFrom<&MetaEvent> for Vec<u8> {
+    type Error = EncodeError;
+
+    /// Encodes `value` as `FF type len data`, the inverse of
+    /// `MetaEvent::try_from`.
+    fn try_from(value: &MetaEvent) -> Result<Self, Self::Error> {
+        let (kind, data) = value.encode();
+        let mut bytes = vec![TRACK_EVENT_STATUS_FF_META, kind];
+        push_variable_length_quantity(&mut bytes, data.len() as u64)
+            .ok_or(EncodeError::DataTooLong)?;
+        bytes.extend(data);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The bytes of a meta-event of `kind` and `data` as written to a file.
+    fn meta_bytes(kind: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![TRACK_EVENT_STATUS_FF_META, kind, data.len() as u8];
+        bytes.extend(data);
+        bytes
+    }
+
+    fn meta_event(kind: u8, data: &[u8]) -> Result<MetaEvent, TryFromError> {
+        MetaEvent::try_from(&MetaEventFile {
+            status: &TRACK_EVENT_STATUS_FF_META,
+            kind: &kind,
+            length: data.len() as u32,
+            data,
+        })
+    }
+
+    #[test]
+    fn text_with_invalid_utf_8_round_trips() {
+        let data = [b'a', 0xFF, 0xFE, b'b'];
+
+        let meta_event = meta_event(0x01, &data).unwrap();
+
+        assert!(
+            matches!(&meta_event, MetaEvent::TextEvent(text) if text.as_str() == "a\u{FFFD}\u{FFFD}b")
+        );
+        assert_eq!(Vec::try_from(&meta_event).unwrap(), meta_bytes(0x01, &data));
+    }
+
+    #[test]
+    fn data_of_wrong_length_is_an_error() {
+        assert!(matches!(
+            meta_event(0x51, &[0x07, 0xA1]),
+            Err(TryFromError::InvalidData)
+        ));
+        assert!(matches!(
+            meta_event(0x51, &[0x07, 0xA1, 0x20, 0x00]),
+            Err(TryFromError::InvalidScannerState)
+        ));
+        assert!(matches!(
+            meta_event(0x2F, &[0x00]),
+            Err(TryFromError::InvalidScannerState)
+        ));
+        assert!(matches!(
+            meta_event(0x51, &[0x07, 0xA1, 0x20]),
+            Ok(MetaEvent::SetTempo(500_000))
+        ));
+    }
+
+    #[test]
+    fn key_signature_with_flats_round_trips() {
+        let data = [0xFD, 0x01];
+
+        let meta_event = meta_event(0x59, &data).unwrap();
+
+        assert!(matches!(
+            meta_event,
+            MetaEvent::KeySignature {
+                sharps_flats: -3,
+                major_minor: 1
+            }
+        ));
+        assert_eq!(Vec::try_from(&meta_event).unwrap(), meta_bytes(0x59, &data));
+    }
+
+    #[test]
+    fn text_range_and_unknown_types_round_trip() {
+        for (kind, data) in [
+            (0x60, &[0x01, 0x02][..]),
+            (0x09, &b"hi"[..]),
+            (0x7F, &[0x00, 0x00, 0x41][..]),
+        ] {
+            let meta_event = meta_event(kind, data).unwrap();
+
+            assert_eq!(Vec::try_from(&meta_event).unwrap(), meta_bytes(kind, data));
+        }
+
+        assert_eq!(
+            meta_event(0x60, &[0x01, 0x02]).unwrap(),
+            MetaEvent::Unknown {
+                status: 0x60,
+                data: vec![0x01, 0x02]
+            }
+        );
+        assert!(matches!(
+            meta_event(0x09, b"hi").unwrap(),
+            MetaEvent::ReservedText { kind: 0x09, text } if text.as_str() == "hi"
+        ));
+        assert_eq!(
+            meta_event(0x7F, &[0x00, 0x00, 0x41]).unwrap(),
+            MetaEvent::SequencerSpecific(vec![0x00, 0x00, 0x41])
+        );
+    }
+
+    #[test]
+    fn unknown_type_is_not_an_error() {
+        assert_eq!(
+            meta_event(0x4B, &[]).unwrap(),
+            MetaEvent::Unknown {
+                status: 0x4B,
+                data: vec![]
+            }
+        );
+        assert!(meta_event(0x51, &[0x01, 0x02]).is_err());
+    }
+}
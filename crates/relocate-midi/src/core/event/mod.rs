@@ -1,30 +1,226 @@
+use ::core::hash::Hasher;
+use alloc::{vec, vec::Vec};
+
 use derive_more::{Debug, Display, Error};
 
-use crate::file::event::track::EventFile;
+use crate::{
+    file::event::track::{EventFile, TRACK_EVENT_STATUS_FF_META},
+    options::ParseOptions,
+    writer::variable_length_quantity_len,
+};
 
+pub mod channel;
+pub mod controller;
 pub mod meta;
+pub mod rpn;
+pub mod sysex;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
     Meta(meta::MetaEvent),
+    Channel(channel::ChannelEvent),
+    SysEx(sysex::SysExEvent),
+}
+
+impl Event {
+    /// Creates an [`Event::Channel`] from a channel voice message `status` and
+    /// its `data` bytes, such as `Event::midi(0xC0, &[5])` for a program
+    /// change on the first channel.
+    ///
+    /// Fails with [`channel::TryFromError::InvalidDataLength`] unless `data`
+    /// holds exactly as many bytes as `status` requires, see
+    /// [`channel::channel_message_data_len`],
+    /// so that the event can be written back to a file.
+    pub fn midi(status: u8, data: &[u8]) -> Result<Self, channel::TryFromError> {
+        channel::ChannelEvent::from_parts(status, data).map(Event::Channel)
+    }
 }
 
 #[derive(Debug, Display, Error)]
 pub enum TryFromError {
     MetaEventFileToMetaEvent(meta::TryFromError),
+    MIDIEventFileToChannelEvent(channel::TryFromError),
+    SysExEventFileToSysExEvent(sysex::TryFromError),
 }
 
 impl<'a> TryFrom<&'a EventFile<'a>> for Event {
     type Error = TryFromError;
 
     fn try_from(value: &EventFile) -> Result<Self, Self::Error> {
+        Event::try_from_with_options(value, &ParseOptions::default())
+    }
+}
+
+impl Event {
+    /// Converts an [`EventFile`] like `Event::try_from`, following `options`.
+    pub fn try_from_with_options(
+        value: &EventFile,
+        options: &ParseOptions,
+    ) -> Result<Self, TryFromError> {
         match value {
             EventFile::Meta(meta_event_file) => {
-                let meta_event = meta::MetaEvent::try_from(meta_event_file)
+                let meta_event = meta::MetaEvent::try_from_with_options(meta_event_file, options)
                     .map_err(TryFromError::MetaEventFileToMetaEvent)?;
                 Ok(Event::Meta(meta_event))
             }
-            _ => todo!(), // TODO: Implement conversion for SysEx and MIDI events
+            EventFile::Midi(midi_event_file) => {
+                let channel_event = channel::ChannelEvent::try_from(midi_event_file)
+                    .map_err(TryFromError::MIDIEventFileToChannelEvent)?;
+                Ok(Event::Channel(channel_event))
+            }
+            EventFile::SysEx(sys_ex_event_file) => {
+                let sys_ex_event = sysex::SysExEvent::try_from(sys_ex_event_file)
+                    .map_err(TryFromError::SysExEventFileToSysExEvent)?;
+                Ok(Event::SysEx(sys_ex_event))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    MetaEventToBytes(meta::EncodeError),
+    SysExEventToBytes(sysex::EncodeError),
+}
+
+impl TryFrom<&Event> for Vec<u8> {
+    type Error = EncodeError;
+
+    fn try_from(value: &Event) -> Result<Self, Self::Error> {
+        match value {
+            Event::Meta(meta_event) => {
+                Vec::try_from(meta_event).map_err(EncodeError::MetaEventToBytes)
+            }
+            Event::Channel(channel_event) => Ok(Vec::from(channel_event)),
+            Event::SysEx(sys_ex_event) => {
+                Vec::try_from(sys_ex_event).map_err(EncodeError::SysExEventToBytes)
+            }
+        }
+    }
+}
+
+impl Event {
+    /// The number of bytes this [`Event`] takes in a file, even if its data is
+    /// too long to be written to one.
+    pub(crate) fn byte_len(&self) -> usize {
+        let with_data = |prefix_len: usize, data_len: usize| {
+            prefix_len + variable_length_quantity_len(data_len as u64) + data_len
+        };
+        match self {
+            Event::Meta(meta_event) => with_data(2, meta_event.encode().1.len()),
+            Event::Channel(channel_event) => channel_event.encode().1,
+            Event::SysEx(sys_ex_event) => with_data(1, sys_ex_event.data.len()),
+        }
+    }
+
+    /// Feeds this [`Event`] to `hasher` as written to a file, or, if its data
+    /// is too long to be written to one, with the length of its data as a
+    /// little-endian `u64` rather than a variable-length quantity.
+    pub(crate) fn hash_content(&self, hasher: &mut impl Hasher) {
+        if let Ok(bytes) = Vec::try_from(self) {
+            hasher.write(&bytes);
+            return;
         }
+        let (prefix, data) = match self {
+            Event::Meta(meta_event) => {
+                let (kind, data) = meta_event.encode();
+                (vec![TRACK_EVENT_STATUS_FF_META, kind], data)
+            }
+            Event::Channel(channel_event) => (Vec::from(channel_event), Vec::new()),
+            Event::SysEx(sys_ex_event) => (vec![sys_ex_event.status()], sys_ex_event.data.clone()),
+        };
+        hasher.write(&prefix);
+        hasher.write(&(data.len() as u64).to_le_bytes());
+        hasher.write(&data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{
+        core::event::{
+            channel::{Channel, ChannelEvent, ChannelMessage},
+            meta::MetaEvent,
+            sysex::{SysExEvent, SysExEventKind},
+        },
+        file::event::track::{
+            MIDIEventFile, MetaEventFile, SysExEventFile, TRACK_EVENT_STATUS_F0_SOX,
+            TRACK_EVENT_STATUS_F7_EOX, TRACK_EVENT_STATUS_FF_META,
+        },
+    };
+
+    #[test]
+    fn converts_each_event_file_variant() {
+        let meta = EventFile::Meta(MetaEventFile {
+            status: &TRACK_EVENT_STATUS_FF_META,
+            kind: &0x2F,
+            length: 0,
+            data: &[],
+        });
+        let midi = EventFile::Midi(MIDIEventFile {
+            status: &0x91,
+            data: &[0x3C, 0x40],
+        });
+        let sys_ex = EventFile::SysEx(SysExEventFile {
+            status: &TRACK_EVENT_STATUS_F0_SOX,
+            length: 3,
+            data: &[0x7E, 0x01, 0xF7],
+        });
+        let escape = EventFile::SysEx(SysExEventFile {
+            status: &TRACK_EVENT_STATUS_F7_EOX,
+            length: 1,
+            data: &[0xF8],
+        });
+
+        assert_eq!(
+            Event::try_from(&meta).unwrap(),
+            Event::Meta(MetaEvent::EndOfTrack)
+        );
+        assert_eq!(
+            Event::try_from(&midi).unwrap(),
+            Event::Channel(ChannelEvent {
+                channel: Channel::new(1).unwrap(),
+                message: ChannelMessage::NoteOn {
+                    key: 0x3C,
+                    velocity: 0x40,
+                },
+            })
+        );
+        assert_eq!(
+            Event::try_from(&sys_ex).unwrap(),
+            Event::SysEx(SysExEvent {
+                kind: SysExEventKind::Normal,
+                data: vec![0x7E, 0x01, 0xF7],
+            })
+        );
+        assert_eq!(
+            Event::try_from(&escape).unwrap(),
+            Event::SysEx(SysExEvent {
+                kind: SysExEventKind::Escape,
+                data: vec![0xF8],
+            })
+        );
+    }
+
+    #[test]
+    fn midi_rejects_wrong_number_of_data_bytes() {
+        assert!(matches!(
+            Event::midi(0xC0, &[1, 2]),
+            Err(channel::TryFromError::InvalidDataLength)
+        ));
+        assert!(matches!(
+            Event::midi(0x90, &[60]),
+            Err(channel::TryFromError::InvalidDataLength)
+        ));
+        assert_eq!(
+            Event::midi(0xC3, &[5]).unwrap(),
+            Event::Channel(ChannelEvent {
+                channel: Channel::new(3).unwrap(),
+                message: ChannelMessage::ProgramChange { program: 5 },
+            })
+        );
     }
 }
@@ -0,0 +1,208 @@
+//! A module reconstructing Registered and Non-Registered Parameter Number
+//! changes from the control change messages that encode them.
+//!
+//! A parameter is selected with [`Controller::RPN_MSB`] and
+//! [`Controller::RPN_LSB`] (or [`Controller::NRPN_MSB`] and
+//! [`Controller::NRPN_LSB`]), then set with [`Controller::DATA_ENTRY_MSB`]
+//! and optionally [`Controller::DATA_ENTRY_LSB`]. For instance, setting the
+//! pitch bend sensitivity (RPN `0`) to 2 semitones on channel 1 is sent as:
+//!
+//! ```text
+//! B0 65 00   RPN MSB = 0
+//! B0 64 00   RPN LSB = 0
+//! B0 06 02   Data Entry MSB = 2 semitones
+//! B0 26 00   Data Entry LSB = 0 cents
+//! ```
+
+use derive_more::Debug;
+
+use crate::core::event::{
+    channel::{Channel, ChannelEvent, ChannelMessage},
+    controller::Controller,
+};
+
+/// The 14-bit "null" parameter number, which deselects any parameter so that
+/// further data entry is ignored.
+pub const NULL_PARAMETER_NUMBER: u16 = 0x3FFF;
+
+/// Whether a parameter number is registered by the MIDI specification or
+/// defined by the manufacturer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterKind {
+    Rpn,
+    Nrpn,
+}
+
+/// A parameter of `channel` set by a data entry message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterChange {
+    /// The MIDI channel the parameter belongs to.
+    pub channel: Channel,
+
+    pub kind: ParameterKind,
+
+    /// The 14-bit parameter number, `msb << 7 | lsb`.
+    pub param: u16,
+
+    /// The 14-bit parameter value, `msb << 7 | lsb`.
+    pub value: u16,
+}
+
+/// The parameter selection and data entry state of a single channel.
+#[derive(Debug, Clone, Copy, Default)]
+struct ChannelState {
+    kind: Option<ParameterKind>,
+    param_msb: Option<u8>,
+    param_lsb: Option<u8>,
+    value_msb: u8,
+}
+
+/// Tracks parameter selection on every channel, turning data entry control
+/// changes into [`ParameterChange`]s.
+///
+/// Control changes must be pushed in time order. Data entry is only reported
+/// once both halves of the parameter number are selected, and never while
+/// the null parameter number is selected.
+#[derive(Debug, Default)]
+pub struct RpnTracker {
+    channels: [ChannelState; 16],
+}
+
+impl RpnTracker {
+    /// Creates a new `RpnTracker` with no parameter selected on any channel.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a [`ChannelEvent`] into the tracker, returning the
+    /// [`ParameterChange`] it completes, if any.
+    ///
+    /// A [`Controller::DATA_ENTRY_MSB`] reports the value with a least
+    /// significant half of zero, and a following
+    /// [`Controller::DATA_ENTRY_LSB`] reports it again with its full
+    /// precision. Events other than parameter control changes are ignored.
+    pub fn push(&mut self, event: &ChannelEvent) -> Option<ParameterChange> {
+        let ChannelMessage::ControlChange { controller, value } = event.message else {
+            return None;
+        };
+        let state = &mut self.channels[usize::from(u8::from(event.channel))];
+
+        match controller {
+            Controller::RPN_MSB => state.select(ParameterKind::Rpn, Some(value), None),
+            Controller::RPN_LSB => state.select(ParameterKind::Rpn, None, Some(value)),
+            Controller::NRPN_MSB => state.select(ParameterKind::Nrpn, Some(value), None),
+            Controller::NRPN_LSB => state.select(ParameterKind::Nrpn, None, Some(value)),
+            Controller::DATA_ENTRY_MSB => {
+                state.value_msb = value;
+                return state.change(event.channel, 0);
+            }
+            Controller::DATA_ENTRY_LSB => return state.change(event.channel, value),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl ChannelState {
+    /// Selects one half of a parameter number of `kind`, forgetting the
+    /// selection of the other kind.
+    ///
+    /// Selecting another parameter forgets the most significant half of the
+    /// value, so that a lone [`Controller::DATA_ENTRY_LSB`] does not combine
+    /// with a value meant for the previous parameter.
+    fn select(&mut self, kind: ParameterKind, msb: Option<u8>, lsb: Option<u8>) {
+        if self.kind != Some(kind) {
+            *self = ChannelState {
+                kind: Some(kind),
+                ..ChannelState::default()
+            };
+        }
+        let param_msb = msb.or(self.param_msb);
+        let param_lsb = lsb.or(self.param_lsb);
+        if (param_msb, param_lsb) != (self.param_msb, self.param_lsb) {
+            self.value_msb = 0;
+        }
+        self.param_msb = param_msb;
+        self.param_lsb = param_lsb;
+    }
+
+    /// The [`ParameterChange`] to the selected parameter, if any, with
+    /// `value_lsb` as the least significant half of its value.
+    fn change(&self, channel: Channel, value_lsb: u8) -> Option<ParameterChange> {
+        let param = u16::from(self.param_msb?) << 7 | u16::from(self.param_lsb?);
+        if param == NULL_PARAMETER_NUMBER {
+            return None;
+        }
+        Some(ParameterChange {
+            channel,
+            kind: self.kind?,
+            param,
+            value: u16::from(self.value_msb) << 7 | u16::from(value_lsb),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn control_change(controller: Controller, value: u8) -> ChannelEvent {
+        ChannelEvent {
+            channel: Channel::new(0).unwrap(),
+            message: ChannelMessage::ControlChange { controller, value },
+        }
+    }
+
+    #[test]
+    fn pitch_bend_range_rpn() {
+        let mut rpn_tracker = RpnTracker::new();
+
+        assert_eq!(
+            rpn_tracker.push(&control_change(Controller::RPN_MSB, 0)),
+            None
+        );
+        assert_eq!(
+            rpn_tracker.push(&control_change(Controller::RPN_LSB, 0)),
+            None
+        );
+        let change = rpn_tracker.push(&control_change(Controller::DATA_ENTRY_MSB, 2));
+        let fine_change = rpn_tracker.push(&control_change(Controller::DATA_ENTRY_LSB, 50));
+
+        let pitch_bend_range = ParameterChange {
+            channel: Channel::new(0).unwrap(),
+            kind: ParameterKind::Rpn,
+            param: 0,
+            value: 2 << 7,
+        };
+        assert_eq!(change, Some(pitch_bend_range.clone()));
+        assert_eq!(
+            fine_change,
+            Some(ParameterChange {
+                value: 2 << 7 | 50,
+                ..pitch_bend_range
+            })
+        );
+    }
+
+    #[test]
+    fn selecting_another_parameter_forgets_value() {
+        let mut rpn_tracker = RpnTracker::new();
+        rpn_tracker.push(&control_change(Controller::RPN_MSB, 0));
+        rpn_tracker.push(&control_change(Controller::RPN_LSB, 0));
+        rpn_tracker.push(&control_change(Controller::DATA_ENTRY_MSB, 12));
+
+        rpn_tracker.push(&control_change(Controller::RPN_LSB, 1));
+        let change = rpn_tracker.push(&control_change(Controller::DATA_ENTRY_LSB, 5));
+
+        assert_eq!(
+            change,
+            Some(ParameterChange {
+                channel: Channel::new(0).unwrap(),
+                kind: ParameterKind::Rpn,
+                param: 1,
+                value: 5,
+            })
+        );
+    }
+}
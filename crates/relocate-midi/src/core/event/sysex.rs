@@ -0,0 +1,224 @@
+use alloc::{vec, vec::Vec};
+
+use derive_more::{Debug, Display, Error};
+
+use crate::{
+    file::event::track::{SysExEventFile, TRACK_EVENT_STATUS_F0_SOX, TRACK_EVENT_STATUS_F7_EOX},
+    writer::push_variable_length_quantity,
+};
+
+/// The two forms a system exclusive event takes in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SysExEventKind {
+    /// `F0 len data`: A system exclusive message, or the first packet of one
+    /// split across several events. `F0` is transmitted before `data`, which
+    /// ends with `F7` if the message is complete.
+    Normal,
+
+    /// `F7 len data`: An "escape", whose `data` is transmitted as is, to
+    /// transmit bytes that are not otherwise allowed, such as System
+    /// Real-Time messages.
+    Escape,
+
+    /// `F7 len data`: A following packet of a message split across several
+    /// events, right after a [`SysExEventKind::Normal`] or `Continuation`
+    /// event whose `data` does not end with `F7`. Its `data` ends with `F7`
+    /// if it completes the message.
+    ///
+    /// A single `F7` event cannot tell it from an escape, so
+    /// `SysExEvent::try_from` always yields [`SysExEventKind::Escape`], and
+    /// only converting a whole track chunk yields continuations.
+    Continuation,
+}
+
+/// A system exclusive event, along with its [`SysExEventKind`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SysExEvent {
+    pub kind: SysExEventKind,
+
+    /// The bytes following the length, without the leading `F0` of a
+    /// [`SysExEventKind::Normal`] event.
+    pub data: Vec<u8>,
+}
+
+/// The manufacturer ID opening a system exclusive message, right after `F0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ManufacturerId {
+    /// A 1-byte ID, from `0x01` to `0x7F`.
+    #[debug("Short({:X})", _0)]
+    Short(u8),
+
+    /// A 3-byte ID, whose first byte is `0x00`, holding the other two bytes.
+    #[debug("Extended({:X}, {:X})", _0, _1)]
+    Extended(u8, u8),
+}
+
+impl ManufacturerId {
+    /// The universal ID of non-real-time messages, such as sample dumps.
+    pub const UNIVERSAL_NON_REAL_TIME: ManufacturerId = ManufacturerId::Short(0x7E);
+
+    /// The universal ID of real-time messages, such as MIDI Time Code.
+    pub const UNIVERSAL_REAL_TIME: ManufacturerId = ManufacturerId::Short(0x7F);
+
+    /// Reads the ID at the start of the `data` of a system exclusive message.
+    ///
+    /// Returns `None` if `data` is too short to hold it.
+    pub fn from_data(data: &[u8]) -> Option<Self> {
+        match data {
+            [0x00, high, low, ..] => Some(ManufacturerId::Extended(*high, *low)),
+            [0x00, ..] | [] => None,
+            [id, ..] => Some(ManufacturerId::Short(*id)),
+        }
+    }
+
+    /// The name of the vendor registered for this ID, or of the universal
+    /// messages for `0x7E` and `0x7F`, among a selection of common ones.
+    ///
+    /// Returns `None` for any other ID, registered or not.
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match self {
+            ManufacturerId::Short(0x01) => "Sequential",
+            ManufacturerId::Short(0x04) => "Moog",
+            ManufacturerId::Short(0x06) => "Lexicon",
+            ManufacturerId::Short(0x07) => "Kurzweil",
+            ManufacturerId::Short(0x0F) => "Ensoniq",
+            ManufacturerId::Short(0x10) => "Oberheim",
+            ManufacturerId::Short(0x11) => "Apple",
+            ManufacturerId::Short(0x18) => "E-mu",
+            ManufacturerId::Short(0x40) => "Kawai",
+            ManufacturerId::Short(0x41) => "Roland",
+            ManufacturerId::Short(0x42) => "Korg",
+            ManufacturerId::Short(0x43) => "Yamaha",
+            ManufacturerId::Short(0x44) => "Casio",
+            ManufacturerId::Short(0x47) => "Akai",
+            ManufacturerId::Short(0x7D) => "Non-Commercial",
+            ManufacturerId::Short(0x7E) => "Universal Non-Real Time",
+            ManufacturerId::Short(0x7F) => "Universal Real Time",
+            ManufacturerId::Extended(0x00, 0x0E) => "Alesis",
+            ManufacturerId::Extended(0x20, 0x29) => "Novation",
+            ManufacturerId::Extended(0x20, 0x32) => "Behringer",
+            ManufacturerId::Extended(0x20, 0x3C) => "Elektron",
+            ManufacturerId::Extended(0x20, 0x6B) => "Arturia",
+            ManufacturerId::Extended(0x21, 0x09) => "Native Instruments",
+            _ => return None,
+        };
+        Some(name)
+    }
+}
+
+impl SysExEvent {
+    /// The [`ManufacturerId`] of this [`SysExEvent`], if it is a
+    /// [`SysExEventKind::Normal`] one long enough to hold it.
+    ///
+    /// The `data` of a [`SysExEventKind::Escape`] or
+    /// [`SysExEventKind::Continuation`] event does not start a message, so it
+    /// has none.
+    pub fn manufacturer_id(&self) -> Option<ManufacturerId> {
+        match self.kind {
+            SysExEventKind::Normal => ManufacturerId::from_data(&self.data),
+            SysExEventKind::Escape | SysExEventKind::Continuation => None,
+        }
+    }
+}
+
+/// Tells [`SysExEventKind::Continuation`]s from [`SysExEventKind::Escape`]s
+/// along a track, by remembering whether a split message is unfinished.
+#[derive(Debug, Default)]
+pub(crate) struct SplitSysEx {
+    pending: bool,
+}
+
+impl SplitSysEx {
+    /// Turns `sys_ex_event` into a continuation if it is an escape following
+    /// an unfinished message, and records whether it leaves one unfinished.
+    pub(crate) fn classify(&mut self, sys_ex_event: &mut SysExEvent) {
+        match sys_ex_event.kind {
+            SysExEventKind::Escape if !self.pending => return,
+            SysExEventKind::Escape | SysExEventKind::Continuation => {
+                sys_ex_event.kind = SysExEventKind::Continuation;
+            }
+            SysExEventKind::Normal => {}
+        }
+        self.pending = sys_ex_event.data.last() != Some(&TRACK_EVENT_STATUS_F7_EOX);
+    }
+}
+
+#[derive(Debug, Display, Error)]
+pub enum TryFromError {
+    #[debug("InvalidStatus({:X})", _0)]
+    InvalidStatus(#[error(ignore)] u8),
+}
+
+impl<'a> TryFrom<&'a SysExEventFile<'a>> for SysExEvent {
+    type Error = TryFromError;
+
+    fn try_from(value: &SysExEventFile) -> Result<Self, Self::Error> {
+        let kind = match *value.status {
+            TRACK_EVENT_STATUS_F0_SOX => SysExEventKind::Normal,
+            TRACK_EVENT_STATUS_F7_EOX => SysExEventKind::Escape,
+            status => return Err(TryFromError::InvalidStatus(status)),
+        };
+        Ok(SysExEvent {
+            kind,
+            data: value.data.to_vec(),
+        })
+    }
+}
+
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    /// The data is longer than `0x0FFFFFFF` bytes, the largest length a
+    /// variable-length quantity can hold.
+    DataTooLong,
+}
+
+impl SysExEvent {
+    /// The status byte this [`SysExEvent`] is written with, `F0` or `F7`.
+    pub(crate) fn status(&self) -> u8 {
+        match self.kind {
+            SysExEventKind::Normal => TRACK_EVENT_STATUS_F0_SOX,
+            SysExEventKind::Escape | SysExEventKind::Continuation => TRACK_EVENT_STATUS_F7_EOX,
+        }
+    }
+}
+
+impl TryFrom<&SysExEvent> for Vec<u8> {
+    type Error = EncodeError;
+
+    /// Encodes `value` as `F0 len data` or `F7 len data`, the inverse of
+    /// `SysExEvent::try_from`.
+    fn try_from(value: &SysExEvent) -> Result<Self, Self::Error> {
+        let mut bytes = vec![value.status()];
+        push_variable_length_quantity(&mut bytes, value.data.len() as u64)
+            .ok_or(EncodeError::DataTooLong)?;
+        bytes.extend(&value.data);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_of_registered_and_unknown_ids() {
+        assert_eq!(ManufacturerId::Short(0x01).name(), Some("Sequential"));
+        assert_eq!(ManufacturerId::Short(0x41).name(), Some("Roland"));
+        assert_eq!(ManufacturerId::Short(0x42).name(), Some("Korg"));
+        assert_eq!(ManufacturerId::Short(0x43).name(), Some("Yamaha"));
+        assert_eq!(
+            ManufacturerId::Extended(0x20, 0x29).name(),
+            Some("Novation")
+        );
+        assert_eq!(
+            ManufacturerId::UNIVERSAL_NON_REAL_TIME.name(),
+            Some("Universal Non-Real Time")
+        );
+        assert_eq!(
+            ManufacturerId::UNIVERSAL_REAL_TIME.name(),
+            Some("Universal Real Time")
+        );
+        assert_eq!(ManufacturerId::Short(0x33).name(), None);
+        assert_eq!(ManufacturerId::Extended(0x7F, 0x7F).name(), None);
+    }
+}
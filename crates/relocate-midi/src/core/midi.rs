@@ -1,13 +1,32 @@
+use ::core::{hash::Hasher, time::Duration};
+use alloc::{string::String, vec, vec::Vec};
+
 use derive_more::{Debug, Deref, Display, Error, IntoIterator};
+use log::warn;
 
 use crate::{
-    core::chunk::Chunk,
+    core::{
+        chunk::{
+            Chunk,
+            header::{HeaderChunk, division::Division, format::Format},
+            track::{TrackChunk, TrackEvent},
+        },
+        event::{
+            Event,
+            channel::{ChannelEvent, ChannelMessage},
+            meta::{MetaEvent, TextMeta},
+        },
+        summary::{MIDISummary, TrackSummary},
+        tempo::{DEFAULT_TEMPO, TempoMap},
+        ticks::{AbsoluteTicks, DeltaTicks},
+    },
     file::{chunk::ChunksFile, midi::MIDIFile},
+    options::{AnalysisOptions, ParseOptions},
 };
 
 /// Above of [`MIDIFile`], a [`MIDI`] is a [series of chunks](Vec<Chunk>).
-#[derive(Debug, Deref, IntoIterator)]
-pub struct MIDI(Vec<Chunk>);
+#[derive(Debug, Clone, PartialEq, Eq, Deref, IntoIterator)]
+pub struct MIDI(pub(crate) Vec<Chunk>);
 
 #[derive(Debug, Display, Error)]
 pub enum TryFromError {
@@ -15,6 +34,31 @@ pub enum TryFromError {
     ChunkFileToChunk(crate::core::chunk::TryFromError),
 }
 
+#[derive(Debug, Display, Error)]
+pub enum ConvertError {
+    MissingHeader,
+    IncompatibleDivision,
+    DeltaTimeOverflow,
+}
+
+#[derive(Debug, Display, Error)]
+pub enum EncodeError {
+    ChunkToBytes(crate::core::chunk::EncodeError),
+}
+
+#[derive(Debug, Display, Error)]
+pub enum MergeError {
+    MissingHeader,
+
+    /// The divisions of the two files differ, so the same delta-time would
+    /// not last as long in both.
+    IncompatibleDivision,
+
+    /// An event of the other file would be further than
+    /// [`DeltaTicks::MAX`] from the previous one once appended.
+    DeltaTimeOverflow,
+}
+
 impl TryFrom<Vec<u8>> for MIDI {
     type Error = TryFromError;
 
@@ -27,15 +71,1437 @@ impl<'a> TryFrom<&'a MIDIFile> for MIDI {
     type Error = TryFromError;
 
     fn try_from(value: &'a MIDIFile) -> Result<Self, Self::Error> {
+        MIDI::try_from_with_options(value, &ParseOptions::default())
+    }
+}
+
+impl MIDI {
+    /// Converts a [`MIDIFile`] like `MIDI::try_from`, following `options`.
+    pub fn try_from_with_options(
+        value: &MIDIFile,
+        options: &ParseOptions,
+    ) -> Result<Self, TryFromError> {
         let mut chunks = Vec::new();
 
-        let chunks_file =
-            ChunksFile::try_from(value).map_err(TryFromError::MIDIFileToChunksFile)?;
+        let chunks_file = ChunksFile::try_from_with_options(value, options)
+            .map_err(TryFromError::MIDIFileToChunksFile)?;
+        warn_trailing(&chunks_file);
         for chunk_file in chunks_file {
-            let chunk = Chunk::try_from(&chunk_file).map_err(TryFromError::ChunkFileToChunk)?;
+            let chunk = Chunk::try_from_with_options(&chunk_file, options)
+                .map_err(TryFromError::ChunkFileToChunk)?;
             chunks.push(chunk);
         }
 
         Ok(MIDI(chunks))
     }
+
+    /// Converts a [`MIDIFile`] like `MIDI::try_from`, but stops at the first
+    /// chunk that cannot be read or converted, returning the chunks converted
+    /// before it along with the error, if any.
+    ///
+    /// This salvages what precedes the damage in a corrupt or truncated file.
+    pub fn try_from_partial(value: &MIDIFile) -> (Vec<Chunk>, Option<TryFromError>) {
+        let options = ParseOptions::default();
+        let mut chunks = Vec::new();
+
+        let (chunks_file, error) = ChunksFile::try_from_partial(value, &options);
+        if error.is_none() {
+            warn_trailing(&chunks_file);
+        }
+        for chunk_file in chunks_file {
+            match Chunk::try_from_with_options(&chunk_file, &options) {
+                Ok(chunk) => chunks.push(chunk),
+                Err(error) => return (chunks, Some(TryFromError::ChunkFileToChunk(error))),
+            }
+        }
+
+        (chunks, error.map(TryFromError::MIDIFileToChunksFile))
+    }
+}
+
+impl MIDI {
+    /// Creates a [`MIDI`] with just a [`HeaderChunk`] of `format` and
+    /// `division`, and no tracks, ready for [`MIDI::push_track`].
+    pub fn new(format: Format, division: Division) -> Self {
+        MIDI(vec![Chunk::Header(HeaderChunk {
+            format,
+            tracks_count: 0,
+            division,
+        })])
+    }
+
+    /// Appends `track` as the last chunk of this [`MIDI`], keeping the
+    /// `tracks_count` of its [`HeaderChunk`], if any, in sync.
+    ///
+    /// A [`Format::SingleMultiChannelTrack`] file holds a single track, so
+    /// pushing another one makes it a [`Format::SimultaneousTracks`] file.
+    pub fn push_track(&mut self, track: TrackChunk) {
+        if self.tracks().next().is_some() {
+            self.promote_to_simultaneous_tracks();
+        }
+        self.0.push(Chunk::Track(track));
+        self.sync_track_count();
+    }
+
+    /// Removes the track at `index` among the tracks of this [`MIDI`], as
+    /// counted by [`MIDI::tracks`], keeping the `tracks_count` of its
+    /// [`HeaderChunk`], if any, in sync.
+    ///
+    /// Returns `None`, leaving this [`MIDI`] unchanged, if there is no such
+    /// track.
+    pub fn remove_track(&mut self, index: usize) -> Option<TrackChunk> {
+        let position = self
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| matches!(chunk, Chunk::Track(_)))
+            .nth(index)?
+            .0;
+        let Chunk::Track(track_chunk) = self.0.remove(position) else {
+            unreachable!("the chunk at position is a track");
+        };
+        self.sync_track_count();
+        Some(track_chunk)
+    }
+
+    /// Sets the `tracks_count` of the [`HeaderChunk`] of this [`MIDI`], if
+    /// any, to the number of its tracks, saturating at `u16::MAX`.
+    ///
+    /// [`MIDI::push_track`] and [`MIDI::remove_track`] already do so; this is
+    /// only needed after `tracks_count` is set directly.
+    pub fn sync_track_count(&mut self) {
+        let tracks_count = u16::try_from(self.tracks().count()).unwrap_or(u16::MAX);
+        for chunk in self.0.iter_mut() {
+            if let Chunk::Header(header_chunk) = chunk {
+                header_chunk.tracks_count = tracks_count;
+                break;
+            }
+        }
+    }
+
+    /// Appends every track of `other` after the tracks of this [`MIDI`], so
+    /// that they play together, as in a [`Format::SimultaneousTracks`] file,
+    /// which this [`MIDI`] becomes.
+    ///
+    /// The alien chunks of `other` are not kept, and channels are kept as is,
+    /// so tracks of both files on the same channel share its programs and
+    /// controllers.
+    ///
+    /// Fails, leaving this [`MIDI`] unchanged, if either file has no
+    /// [`HeaderChunk`] or if their divisions differ, see
+    /// [`MIDI::with_division`] to convert one first.
+    pub fn merge(&mut self, other: &MIDI) -> Result<(), MergeError> {
+        let division = &other.header().ok_or(MergeError::MissingHeader)?.division;
+        let header_chunk = self
+            .0
+            .iter_mut()
+            .find_map(|chunk| match chunk {
+                Chunk::Header(header_chunk) => Some(header_chunk),
+                _ => None,
+            })
+            .ok_or(MergeError::MissingHeader)?;
+        if header_chunk.division != *division {
+            return Err(MergeError::IncompatibleDivision);
+        }
+
+        header_chunk.format = Format::SimultaneousTracks;
+        self.0.extend(other.tracks().cloned().map(Chunk::Track));
+        self.sync_track_count();
+        Ok(())
+    }
+
+    /// Multiplies the value of every [`MetaEvent::SetTempo`] by `factor`, so
+    /// that a `factor` above 1 plays this [`MIDI`] slower without changing
+    /// its pitch or where notes fall in bars and beats.
+    ///
+    /// If there is no [`MetaEvent::SetTempo`], one scaling [`DEFAULT_TEMPO`]
+    /// is inserted at the start of the first track. Scaled tempos are rounded
+    /// and clamped into the 24 bits they are stored in, and at least 1.
+    ///
+    /// Does nothing if `factor` is not finite or not positive.
+    pub fn scale_tempo(&mut self, factor: f64) {
+        if !factor.is_finite() || factor <= 0.0 {
+            return;
+        }
+
+        // `as` saturates, and adding a half rounds to the nearest without
+        // `f64::round`, which `core` lacks.
+        let scale = |tempo: u32| ((f64::from(tempo) * factor + 0.5) as u32).clamp(1, 0xFF_FFFF);
+        let mut has_tempo = false;
+        for chunk in self.0.iter_mut() {
+            let Chunk::Track(track_chunk) = chunk else {
+                continue;
+            };
+            for track_event in track_chunk.0.iter_mut() {
+                if let Event::Meta(MetaEvent::SetTempo(tempo)) = &mut track_event.kind {
+                    *tempo = scale(*tempo);
+                    has_tempo = true;
+                }
+            }
+        }
+
+        if !has_tempo
+            && let Some(Chunk::Track(track_chunk)) = self
+                .0
+                .iter_mut()
+                .find(|chunk| matches!(chunk, Chunk::Track(_)))
+        {
+            track_chunk.0.insert(
+                0,
+                TrackEvent {
+                    delta_time: DeltaTicks(0),
+                    kind: Event::Meta(MetaEvent::SetTempo(scale(DEFAULT_TEMPO))),
+                },
+            );
+        }
+    }
+
+    /// Appends `other` after the end of this [`MIDI`] in time, so that it
+    /// plays once this [`MIDI`] is over, such as to build a medley.
+    ///
+    /// Every track of `other` is joined to the track of this [`MIDI`] with the
+    /// same index, starting at [`MIDI::duration_ticks`] of this [`MIDI`], and
+    /// the `EndOfTrack` of that track is dropped. Extra tracks of `other` are
+    /// pushed as new tracks delayed the same way, making a
+    /// [`Format::SingleMultiChannelTrack`] file a
+    /// [`Format::SimultaneousTracks`] one.
+    ///
+    /// Fails, leaving this [`MIDI`] unchanged, if either file has no
+    /// [`HeaderChunk`], if their divisions differ, see
+    /// [`MIDI::with_division`] to convert one first, or if this [`MIDI`] is
+    /// so long that an event of `other` could not be delayed enough.
+    pub fn append_sequentially(&mut self, other: &MIDI) -> Result<(), MergeError> {
+        let header_chunk = self.header().ok_or(MergeError::MissingHeader)?;
+        let other_header_chunk = other.header().ok_or(MergeError::MissingHeader)?;
+        if header_chunk.division != other_header_chunk.division {
+            return Err(MergeError::IncompatibleDivision);
+        }
+
+        let tick = self.duration_ticks();
+        let mut appended = self.clone();
+        let mut other_tracks = other.tracks();
+        for chunk in appended.0.iter_mut() {
+            if let Chunk::Track(track_chunk) = chunk {
+                let Some(other_track_chunk) = other_tracks.next() else {
+                    break;
+                };
+                track_chunk
+                    .append_at(other_track_chunk, tick)
+                    .map_err(|_| MergeError::DeltaTimeOverflow)?;
+            }
+        }
+        for other_track_chunk in other_tracks {
+            let mut track_chunk = TrackChunk(Vec::new());
+            track_chunk
+                .append_at(other_track_chunk, tick)
+                .map_err(|_| MergeError::DeltaTimeOverflow)?;
+            appended.push_track(track_chunk);
+        }
+        *self = appended;
+        Ok(())
+    }
+
+    /// Makes a [`Format::SingleMultiChannelTrack`] header a
+    /// [`Format::SimultaneousTracks`] one, before another track is pushed, see
+    /// [`MIDI::push_track`].
+    fn promote_to_simultaneous_tracks(&mut self) {
+        for chunk in self.0.iter_mut() {
+            if let Chunk::Header(header_chunk) = chunk {
+                if header_chunk.format == Format::SingleMultiChannelTrack {
+                    header_chunk.format = Format::SimultaneousTracks;
+                }
+                break;
+            }
+        }
+    }
+}
+
+impl MIDI {
+    /// The first [`HeaderChunk`] of this [`MIDI`], if any.
+    pub fn header(&self) -> Option<&HeaderChunk> {
+        self.iter().find_map(|chunk| match chunk {
+            Chunk::Header(header_chunk) => Some(header_chunk),
+            _ => None,
+        })
+    }
+
+    /// Iterates over the [`TrackChunk`]s of this [`MIDI`], in file order.
+    pub fn tracks(&self) -> impl Iterator<Item = &TrackChunk> {
+        self.iter().filter_map(|chunk| match chunk {
+            Chunk::Track(track_chunk) => Some(track_chunk),
+            _ => None,
+        })
+    }
+
+    /// The track holding the tempo map of this [`MIDI`], from which a
+    /// [`TempoMap`] can be built.
+    ///
+    /// In a [`Format::SimultaneousTracks`] file, the first track conventionally
+    /// holds the tempo and time signature changes for all tracks. A
+    /// [`Format::SingleMultiChannelTrack`] file has only one track, which is
+    /// returned. Returns `None` for a
+    /// [`Format::SequentiallyIndependentSingleTrackPatterns`] file, where every
+    /// track has its own tempo, or if there is no header.
+    pub fn tempo_track(&self) -> Option<&TrackChunk> {
+        match self.header()?.format {
+            Format::SingleMultiChannelTrack | Format::SimultaneousTracks => self.tracks().next(),
+            Format::SequentiallyIndependentSingleTrackPatterns => None,
+        }
+    }
+
+    /// Iterates over the [`MetaEvent`]s of every track along with their
+    /// absolute time in ticks.
+    ///
+    /// Tracks are walked one after another, so ticks only increase within a
+    /// track.
+    pub fn meta_events(&self) -> impl Iterator<Item = (u64, &MetaEvent)> {
+        self.tracks().flat_map(|track_chunk| {
+            track_chunk
+                .absolute_events()
+                .filter_map(
+                    |(AbsoluteTicks(tick), track_event)| match &track_event.kind {
+                        Event::Meta(meta_event) => Some((tick, meta_event)),
+                        _ => None,
+                    },
+                )
+        })
+    }
+
+    /// Collects every [`MetaEvent::Lyric`] of every track as a timed
+    /// transcript of `(absolute_tick, text)`, in time order.
+    ///
+    /// Lyrics are usually stored one syllable per event, so each syllable is
+    /// its own entry; simultaneous lyrics keep their track order.
+    pub fn lyrics(&self) -> Vec<(u64, String)> {
+        self.timed_texts(|meta_event| match meta_event {
+            MetaEvent::Lyric(text) => Some(text),
+            _ => None,
+        })
+    }
+
+    /// Collects every [`MetaEvent::Marker`] of every track as
+    /// `(absolute_tick, text)`, in time order.
+    pub fn markers(&self) -> Vec<(u64, String)> {
+        self.timed_texts(|meta_event| match meta_event {
+            MetaEvent::Marker(text) => Some(text),
+            _ => None,
+        })
+    }
+
+    /// Collects the lyrics of a `.kar` karaoke file as a timed transcript of
+    /// `(absolute_tick, text)`, in time order, following the Soft Karaoke
+    /// conventions rather than [`MetaEvent::Lyric`], see [`MIDI::lyrics`].
+    ///
+    /// Lyrics are stored in [`MetaEvent::TextEvent`]s of the words track
+    /// only, where lines starting with `@` carry information such as `@K`
+    /// (file type), `@T` (title) or `@I` (other info) and are skipped. A `/`
+    /// (new line) or `\` (new paragraph) becomes a line feed.
+    ///
+    /// The words track is the first track holding both `@`-prefixed lines and
+    /// other text, or else the third track, where Soft Karaoke files put
+    /// their words. Text events of other tracks, such as comments, are
+    /// ignored.
+    pub fn kar_lyrics(&self) -> Vec<(u64, String)> {
+        let Some(words_track) = self
+            .tracks()
+            .find(|track_chunk| {
+                kar_texts(track_chunk).any(|(_, text)| text.starts_with('@'))
+                    && kar_texts(track_chunk).any(|(_, text)| !text.starts_with('@'))
+            })
+            .or_else(|| self.tracks().nth(2))
+        else {
+            return Vec::new();
+        };
+
+        kar_texts(words_track)
+            .filter(|(_, text)| !text.starts_with('@'))
+            .map(|(tick, text)| (tick, text.replace(['/', '\\'], "\n")))
+            .collect()
+    }
+
+    /// Collects the text `select`ed from meta events, sorted by tick.
+    fn timed_texts(&self, select: impl Fn(&MetaEvent) -> Option<&TextMeta>) -> Vec<(u64, String)> {
+        let mut texts: Vec<(u64, String)> = self
+            .meta_events()
+            .filter_map(|(tick, meta_event)| Some((tick, select(meta_event)?.text.clone())))
+            .collect();
+        texts.sort_by_key(|(tick, _)| *tick);
+        texts
+    }
+
+    /// Whether this [`MIDI`] and `other` hold the same chunks, comparing
+    /// tracks with [`TrackChunk::semantically_eq`].
+    ///
+    /// Unlike `==` on their bytes, this ignores how events were encoded, such
+    /// as with or without running status, and whether tracks end with
+    /// [`MetaEvent::EndOfTrack`].
+    pub fn semantically_eq(&self, other: &MIDI) -> bool {
+        self.len() == other.len()
+            && self.iter().zip(other.iter()).all(|chunks| match chunks {
+                (Chunk::Track(track_chunk), Chunk::Track(other_track_chunk)) => {
+                    track_chunk.semantically_eq(other_track_chunk)
+                }
+                (chunk, other_chunk) => chunk == other_chunk,
+            })
+    }
+
+    /// A hash of the content of this [`MIDI`], equal for any two files that
+    /// are [`MIDI::semantically_eq`], such as to deduplicate a corpus of files
+    /// differing only in how they were encoded.
+    ///
+    /// Tracks are hashed as their events along with their absolute time, and
+    /// other chunks as their bytes. The hash is 64-bit FNV-1a, which is
+    /// stable across platforms and releases but not cryptographic.
+    pub fn content_fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        for chunk in self.iter() {
+            match chunk {
+                Chunk::Track(track_chunk) => track_chunk.hash_content(&mut hasher),
+                Chunk::Header(header_chunk) => header_chunk.hash_content(&mut hasher),
+                Chunk::Alien(alien_chunk) => hasher.write(&Vec::from(alien_chunk)),
+            }
+        }
+        hasher.finish()
+    }
+
+    /// The length of this [`MIDI`] in ticks, that is, the length of its
+    /// longest track.
+    pub fn duration_ticks(&self) -> u64 {
+        self.tracks()
+            .map(TrackChunk::duration_ticks)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The playback length of this [`MIDI`] in seconds, that is, the duration
+    /// of its longest track converted through `tempo_map`.
+    ///
+    /// A time-code-based `division` ignores `tempo_map`, see
+    /// [`TempoMap::seconds_at`].
+    pub fn duration_seconds(&self, tempo_map: &TempoMap, division: &Division) -> f64 {
+        tempo_map.seconds_at(self.duration_ticks(), division)
+    }
+
+    /// The playback length of this [`MIDI`], following its own tempo changes
+    /// and division.
+    ///
+    /// Returns `None` if there is no header or its division is
+    /// time-code-based, whose length is given by
+    /// [`Division::smpte_seconds_at`] instead.
+    pub fn duration(&self) -> Option<Duration> {
+        let division = &self.header()?.division;
+        if let Division::TimeCode { .. } = division {
+            return None;
+        }
+        TempoMap::from(self).duration_at(self.duration_ticks(), division)
+    }
+
+    /// The number of notes started on each pitch class, from C (`0`) to B
+    /// (`11`), that is, of `NoteOn` events with a non-zero velocity by
+    /// `key % 12`, across every track.
+    ///
+    /// Notes on the channels excluded by `options`, usually
+    /// [`DRUM_CHANNEL`](crate::core::event::channel::DRUM_CHANNEL), are not
+    /// counted. The counts are raw, so that callers can normalize them as
+    /// they see fit.
+    pub fn pitch_class_histogram(&self, options: &AnalysisOptions) -> [u32; 12] {
+        let mut histogram = [0; 12];
+        for track_event in self.tracks().flat_map(|track_chunk| track_chunk.iter()) {
+            let Event::Channel(ChannelEvent {
+                channel,
+                message: ChannelMessage::NoteOn { key, velocity },
+            }) = &track_event.kind
+            else {
+                continue;
+            };
+            if *velocity == 0 || !options.includes(*channel) {
+                continue;
+            }
+            histogram[usize::from(key % 12)] += 1;
+        }
+        histogram
+    }
+
+    /// An overview of this [`MIDI`]: its header, playback length and a
+    /// [`TrackSummary`] of every track.
+    pub fn summary(&self) -> MIDISummary {
+        let header_chunk = self.header();
+        MIDISummary {
+            format: header_chunk.map(|header_chunk| header_chunk.format),
+            tracks_count: self.tracks().count(),
+            division: header_chunk.map(|header_chunk| header_chunk.division.clone()),
+            duration: self.duration(),
+            tracks: self.tracks().map(TrackSummary::from).collect(),
+        }
+    }
+
+    /// Iterates over the [`TrackEvent`]s of every track merged in time order,
+    /// along with the time elapsed since the start when they are due,
+    /// following the tempo changes of every track.
+    ///
+    /// Simultaneous events keep their track order. Yields nothing if there is
+    /// no header.
+    pub fn scheduled_events(&self) -> impl Iterator<Item = (Duration, &TrackEvent)> {
+        let mut track_events: Vec<(AbsoluteTicks, &TrackEvent)> = self
+            .tracks()
+            .flat_map(|track_chunk| track_chunk.absolute_events())
+            .collect();
+        track_events.sort_by_key(|(tick, _)| *tick);
+
+        let tempo_map = TempoMap::from(self);
+        let division = self.header().map(|header_chunk| &header_chunk.division);
+        track_events
+            .into_iter()
+            .filter_map(move |(AbsoluteTicks(tick), track_event)| {
+                Some((tempo_map.duration_at(tick, division?)?, track_event))
+            })
+    }
+
+    /// Returns a copy of this [`MIDI`] using `division` as its timebase, with
+    /// every delta-time rescaled so that events keep their musical position.
+    ///
+    /// Rescaling is only defined between two metrical divisions. Going from
+    /// metrical to time-code-based time (or back) needs a tempo assumption, so
+    /// it fails with [`ConvertError::IncompatibleDivision`] unless `division`
+    /// is already the current one.
+    pub fn with_division(&self, division: Division) -> Result<MIDI, ConvertError> {
+        let header_chunk = self.header().ok_or(ConvertError::MissingHeader)?;
+        let (denominator, numerator) = match (&header_chunk.division, &division) {
+            (Division::TicksPerQuarterNote(from), Division::TicksPerQuarterNote(to)) => {
+                (u64::from(*from), u64::from(*to))
+            }
+            (from, to) if from == to => (1, 1),
+            _ => return Err(ConvertError::IncompatibleDivision),
+        };
+
+        let mut chunks = Vec::with_capacity(self.0.len());
+        for chunk in self.iter() {
+            let chunk = match chunk {
+                Chunk::Header(header_chunk) => Chunk::Header(HeaderChunk {
+                    division: division.clone(),
+                    ..header_chunk.clone()
+                }),
+                Chunk::Track(track_chunk) => Chunk::Track(
+                    track_chunk
+                        .rescaled(numerator, denominator)
+                        .ok_or(ConvertError::DeltaTimeOverflow)?,
+                ),
+                Chunk::Alien(alien_chunk) => Chunk::Alien(alien_chunk.clone()),
+            };
+            chunks.push(chunk);
+        }
+
+        Ok(MIDI(chunks))
+    }
+}
+
+/// The 64-bit FNV-1a hash, whose output unlike that of `DefaultHasher` is
+/// specified, so that [`MIDI::content_fingerprint`] can be stored.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xCBF2_9CE4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl MIDI {
+    /// Writes every event of this [`MIDI`] to `writer` as CSV, one row per
+    /// event in track order, after the header row
+    /// `track,absolute_tick,event_type,channel,data1,data2,meta_type,text`.
+    ///
+    /// - `track` is the index of the track, counted from 0.
+    /// - `event_type` is the [`ChannelMessage`] variant name, `Meta` or
+    ///   `SysEx`.
+    /// - `channel` is the [`Channel`](crate::core::event::channel::Channel)
+    ///   of a channel event, counted from 0.
+    /// - `data1` and `data2` are the data of a channel event, such as the key
+    ///   and velocity of a note, or the 14-bit value of a pitch bend.
+    /// - `meta_type` is the type byte of a meta-event, and `text` the text of
+    ///   a text-like one, quoted if it contains commas, quotes or line breaks.
+    ///
+    /// Fields that do not apply to an event are left empty.
+    pub fn to_csv<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "track,absolute_tick,event_type,channel,data1,data2,meta_type,text"
+        )?;
+        for (track, track_chunk) in self.tracks().enumerate() {
+            for (AbsoluteTicks(tick), track_event) in track_chunk.absolute_events() {
+                let (event_type, channel, data1, data2, meta_type, text) = match &track_event.kind {
+                    Event::Channel(ChannelEvent { channel, message }) => {
+                        let (event_type, data1, data2) = match *message {
+                            ChannelMessage::NoteOff { key, velocity } => {
+                                ("NoteOff", u16::from(key), Some(velocity))
+                            }
+                            ChannelMessage::NoteOn { key, velocity } => {
+                                ("NoteOn", u16::from(key), Some(velocity))
+                            }
+                            ChannelMessage::PolyphonicKeyPressure { key, pressure } => {
+                                ("PolyphonicKeyPressure", u16::from(key), Some(pressure))
+                            }
+                            ChannelMessage::ControlChange { controller, value } => {
+                                ("ControlChange", u16::from(controller.0), Some(value))
+                            }
+                            ChannelMessage::ProgramChange { program } => {
+                                ("ProgramChange", u16::from(program), None)
+                            }
+                            ChannelMessage::ChannelPressure { pressure } => {
+                                ("ChannelPressure", u16::from(pressure), None)
+                            }
+                            ChannelMessage::PitchBend { value } => ("PitchBend", value, None),
+                        };
+                        let channel = Some(u8::from(*channel));
+                        (event_type, channel, Some(data1), data2, None, None)
+                    }
+                    Event::Meta(meta_event) => {
+                        let text = meta_event.text().map(TextMeta::as_str);
+                        ("Meta", None, None, None, Some(meta_event.encode().0), text)
+                    }
+                    Event::SysEx(_) => ("SysEx", None, None, None, None, None),
+                };
+                writeln!(
+                    writer,
+                    "{track},{tick},{event_type},{},{},{},{},{}",
+                    csv_field(channel),
+                    csv_field(data1),
+                    csv_field(data2),
+                    csv_field(meta_type),
+                    csv_field(text.map(csv_escape)),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `value` as a CSV field, empty if `None`.
+#[cfg(feature = "std")]
+fn csv_field(value: Option<impl ::core::fmt::Display>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// `text` as a CSV field, quoted with its quotes doubled if it contains a
+/// comma, a quote or a line break.
+#[cfg(feature = "std")]
+fn csv_escape(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Warns that the [`ChunksFile::trailing`] bytes of `chunks_file`, if any, are
+/// dropped, since a [`MIDI`] has nowhere to keep them.
+fn warn_trailing(chunks_file: &ChunksFile) {
+    if !chunks_file.trailing.is_empty() {
+        warn!(
+            "Ignoring {} trailing bytes after the last chunk.",
+            chunks_file.trailing.len()
+        );
+    }
+}
+
+/// The text of every [`MetaEvent::TextEvent`] of `track_chunk` along with its
+/// absolute time in ticks, see [`MIDI::kar_lyrics`].
+fn kar_texts(track_chunk: &TrackChunk) -> impl Iterator<Item = (u64, &str)> {
+    track_chunk.absolute_events().filter_map(
+        |(AbsoluteTicks(tick), track_event)| match &track_event.kind {
+            Event::Meta(MetaEvent::TextEvent(text)) => Some((tick, text.as_str())),
+            _ => None,
+        },
+    )
+}
+
+impl TryFrom<&MIDI> for Vec<u8> {
+    type Error = EncodeError;
+
+    /// Writes every [`Chunk`] of `value` in order, so that [`Chunk::Alien`]s
+    /// survive a round trip in their original position.
+    fn try_from(value: &MIDI) -> Result<Self, Self::Error> {
+        let mut bytes = Vec::with_capacity(value.iter().map(Chunk::byte_len).sum());
+        for chunk in value.iter() {
+            bytes.extend(Vec::try_from(chunk).map_err(EncodeError::ChunkToBytes)?);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::channel::{ChannelSet, DRUM_CHANNEL};
+
+    /// A file with a header of `format` and `division`, and `tracks` as the
+    /// events of its track chunks.
+    fn midi(format: u16, division: u16, tracks: &[&[u8]]) -> MIDI {
+        let mut bytes = b"MThd\0\0\0\x06".to_vec();
+        bytes.extend(format.to_be_bytes());
+        bytes.extend((tracks.len() as u16).to_be_bytes());
+        bytes.extend(division.to_be_bytes());
+        for track in tracks {
+            bytes.extend(b"MTrk");
+            bytes.extend((track.len() as u32).to_be_bytes());
+            bytes.extend(*track);
+        }
+        MIDI::try_from(bytes).unwrap()
+    }
+
+    const LAPIS_LAZULI: &[u8] = include_bytes!("../../../../assets/Lapis Lazuli.mid");
+    const WORLD_VANQUISHER: &[u8] = include_bytes!("../../../../assets/World Vanquisher.mid");
+
+    fn delta_times(track_chunk: &TrackChunk) -> Vec<u32> {
+        track_chunk
+            .iter()
+            .map(|track_event| track_event.delta_time.0)
+            .collect()
+    }
+
+    #[test]
+    fn bundled_assets_round_trip() {
+        for bytes in [LAPIS_LAZULI, WORLD_VANQUISHER] {
+            let midi = MIDI::try_from(bytes.to_vec()).unwrap();
+
+            let written = Vec::try_from(&midi).unwrap();
+
+            assert_eq!(MIDI::try_from(written).unwrap(), midi);
+        }
+    }
+
+    #[test]
+    fn meta_events_yields_track_name() {
+        let midi = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0xFF, 0x03, 0x04, b'L', b'e', b'a', b'd', 0x60, 0xFF, 0x01, 0x01, b'a', 0x30,
+                0xFF, 0x2F, 0x00,
+            ]],
+        );
+
+        let track_name = midi
+            .meta_events()
+            .find_map(|(tick, meta_event)| match meta_event {
+                MetaEvent::SequenceOrTrackName(text) => Some((tick, text.as_str())),
+                _ => None,
+            });
+
+        assert_eq!(track_name, Some((0, "Lead")));
+        assert_eq!(midi.meta_events().count(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "encoding")]
+    fn try_from_with_options_decodes_shift_jis() {
+        // `カラオケ` in Shift-JIS.
+        let track = [
+            0x00, 0xFF, 0x03, 0x08, 0x83, 0x4A, 0x83, 0x89, 0x83, 0x49, 0x83, 0x50, 0x00, 0xFF,
+            0x2F, 0x00,
+        ];
+        let mut bytes = b"MThd\0\0\0\x06\0\0\0\x01\0\x60MTrk\0\0\0\x10".to_vec();
+        bytes.extend(track);
+        let options = ParseOptions {
+            text_encoding: Some(encoding_rs::SHIFT_JIS),
+            ..ParseOptions::default()
+        };
+
+        let midi = MIDI::try_from_with_options(&MIDIFile::from(bytes), &options).unwrap();
+
+        assert!(matches!(
+            midi.meta_events().next(),
+            Some((0, MetaEvent::SequenceOrTrackName(text))) if text.as_str() == "カラオケ"
+        ));
+    }
+
+    #[test]
+    fn lyrics_are_in_time_order() {
+        let midi = midi(
+            1,
+            96,
+            &[
+                &[
+                    0x00, 0xFF, 0x05, 0x02, b'H', b'e', 0x60, 0xFF, 0x05, 0x02, b'w', b'o', 0x00,
+                    0xFF, 0x2F, 0x00,
+                ],
+                &[
+                    0x30, 0xFF, 0x05, 0x03, b'l', b'l', b'o', 0x60, 0xFF, 0x05, 0x03, b'r', b'l',
+                    b'd', 0x00, 0xFF, 0x2F, 0x00,
+                ],
+            ],
+        );
+
+        assert_eq!(
+            midi.lyrics(),
+            [
+                (0x00, "He".into()),
+                (0x30, "llo".into()),
+                (0x60, "wo".into()),
+                (0x90, "rld".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn alien_chunk_between_tracks_round_trips() {
+        let bytes = [
+            &b"MThd\0\0\0\x06\0\x01\0\x02\0\x60"[..],
+            b"MTrk\0\0\0\x04\0\xFF\x2F\0",
+            b"XFIH\0\0\0\x03\x01\x02\x03",
+            b"MTrk\0\0\0\x04\0\xFF\x2F\0",
+        ]
+        .concat();
+
+        let midi = MIDI::try_from(bytes.clone()).unwrap();
+
+        assert!(matches!(&midi[2], Chunk::Alien(alien_chunk) if alien_chunk.kind == *b"XFIH"));
+        assert_eq!(Vec::try_from(&midi).unwrap(), bytes);
+    }
+
+    #[test]
+    fn with_division_rescales_delta_times() {
+        let midi = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0x90, 0x3C, 0x40, 0x60, 0x80, 0x3C, 0x40, 0x30, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+
+        let midi = midi
+            .with_division(Division::TicksPerQuarterNote(480))
+            .unwrap();
+
+        assert_eq!(
+            midi.header().unwrap().division,
+            Division::TicksPerQuarterNote(480)
+        );
+        assert_eq!(delta_times(midi.tracks().next().unwrap()), [0, 480, 240]);
+    }
+
+    #[test]
+    fn with_division_rejects_delta_time_overflow() {
+        // A delta-time of `DeltaTicks::MAX`, which no longer fits once
+        // doubled.
+        let midi = midi(0, 96, &[&[0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0x2F, 0x00]]);
+
+        assert!(matches!(
+            midi.with_division(Division::TicksPerQuarterNote(192)),
+            Err(ConvertError::DeltaTimeOverflow)
+        ));
+    }
+
+    #[test]
+    fn bundled_asset_has_positive_duration() {
+        let midi = MIDI::try_from(LAPIS_LAZULI.to_vec()).unwrap();
+        let tempo_map = TempoMap::from(&midi);
+
+        let seconds = midi.duration_seconds(&tempo_map, &midi.header().unwrap().division);
+
+        assert!(midi.duration_ticks() > 0);
+        assert!(seconds > 0.0, "{seconds}");
+    }
+
+    /// Parses `bytes` with and without recovery, like the `parse` fuzz
+    /// target, checking that whatever parses writes back into bytes that
+    /// parse again.
+    fn parse_without_panicking(bytes: Vec<u8>) {
+        let midi_file = MIDIFile::from(bytes);
+        let lenient = ParseOptions {
+            recover_chunk_lengths: true,
+            synthesize_end_of_track: true,
+            ..ParseOptions::default()
+        };
+        for options in [ParseOptions::default(), lenient] {
+            if let Ok(midi) = MIDI::try_from_with_options(&midi_file, &options) {
+                assert!(MIDI::try_from(Vec::try_from(&midi).unwrap()).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_input_does_not_panic() {
+        // A xorshift generator, so that failures are reproducible.
+        let mut state: u64 = 0x1234_5678_9ABC_DEF1;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+
+        for _ in 0..2_000 {
+            let asset = if next() % 2 == 0 {
+                LAPIS_LAZULI
+            } else {
+                WORLD_VANQUISHER
+            };
+            let mut bytes = asset[..next() % 4_000].to_vec();
+            for _ in 0..next() % 8 {
+                if !bytes.is_empty() {
+                    let index = next() % bytes.len();
+                    bytes[index] = next() as u8;
+                }
+            }
+            parse_without_panicking(bytes);
+
+            let mut bytes = b"MThd\0\0\0\x06\0\x01\0\x01\0\x60MTrk".to_vec();
+            let length = next() % 64;
+            bytes.extend((length as u32).to_be_bytes());
+            bytes.extend((0..length).map(|_| next() as u8));
+            parse_without_panicking(bytes);
+        }
+    }
+
+    #[test]
+    fn bundled_assets_last_a_few_minutes() {
+        for bytes in [LAPIS_LAZULI, WORLD_VANQUISHER] {
+            let midi = MIDI::try_from(bytes.to_vec()).unwrap();
+
+            let duration = midi.duration().unwrap();
+
+            assert!((60..600).contains(&duration.as_secs()), "{duration:?}");
+        }
+    }
+
+    #[test]
+    fn tempo_change_stretches_later_events() {
+        // 500 ms per quarter note, then 1 s from tick 96 on.
+        let midi = midi(
+            1,
+            96,
+            &[
+                &[
+                    0x60, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, 0x00, 0xFF, 0x2F, 0x00,
+                ],
+                &[
+                    0x00, 0xFF, 0x21, 0x01, 0x00, 0x30, 0xFF, 0x21, 0x01, 0x01, 0x30, 0xFF, 0x21,
+                    0x01, 0x02, 0x30, 0xFF, 0x21, 0x01, 0x03, 0x30, 0xFF, 0x21, 0x01, 0x04, 0x00,
+                    0xFF, 0x2F, 0x00,
+                ],
+            ],
+        );
+
+        let scheduled_events: Vec<(Duration, &TrackEvent)> = midi.scheduled_events().collect();
+        let port_times: Vec<u128> = scheduled_events
+            .iter()
+            .filter(|(_, track_event)| {
+                matches!(track_event.kind, Event::Meta(MetaEvent::MIDIPort(_)))
+            })
+            .map(|(time, _)| time.as_millis())
+            .collect();
+
+        assert_eq!(port_times, vec![0, 250, 500, 1000, 1500]);
+        assert!(scheduled_events.is_sorted_by_key(|(time, _)| *time));
+    }
+
+    #[test]
+    fn tempo_track_of_format_1_is_first_track() {
+        let tracks: [&[u8]; 2] = [
+            &[
+                0x00, 0xFF, 0x51, 0x03, 0x06, 0x1A, 0x80, 0x00, 0xFF, 0x2F, 0x00,
+            ],
+            &[0x0A, 0xFF, 0x21, 0x01, 0x01, 0x00, 0xFF, 0x2F, 0x00],
+        ];
+        let midi_1 = midi(1, 96, &tracks);
+        let midi_2 = midi(2, 96, &tracks);
+
+        let tempo_track = midi_1.tempo_track().unwrap();
+
+        assert_eq!(Some(tempo_track), midi_1.tracks().next());
+        assert_eq!(TempoMap::from(tempo_track).tempo_at(5), 400_000);
+        assert_eq!(midi_2.tempo_track(), None);
+    }
+
+    /// The events of a track chunk holding a [`MetaEvent::TextEvent`] of each
+    /// `(delta_time, text)`.
+    fn text_events(texts: &[(u8, &str)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (delta_time, text) in texts {
+            bytes.extend([*delta_time, 0xFF, 0x01, text.len() as u8]);
+            bytes.extend(text.as_bytes());
+        }
+        bytes.extend([0x00, 0xFF, 0x2F, 0x00]);
+        bytes
+    }
+
+    #[test]
+    fn kar_lyrics_reads_words_track_only() {
+        let midi = midi(
+            1,
+            96,
+            &[
+                &text_events(&[(0, "Not a lyric")]),
+                &text_events(&[(0, "@KMIDI KARAOKE FILE"), (0, "@V0100")]),
+                &text_events(&[
+                    (0, "@LENGL"),
+                    (0, "@TSong"),
+                    (10, "\\Hel"),
+                    (10, "lo"),
+                    (10, "/world"),
+                ]),
+            ],
+        );
+
+        assert_eq!(
+            midi.kar_lyrics(),
+            vec![
+                (10, String::from("\nHel")),
+                (20, String::from("lo")),
+                (30, String::from("\nworld")),
+            ]
+        );
+        assert!(midi.lyrics().is_empty());
+    }
+
+    #[test]
+    fn kar_lyrics_falls_back_to_third_track() {
+        let midi = midi(
+            1,
+            96,
+            &[
+                &text_events(&[(0, "Not a lyric")]),
+                &text_events(&[]),
+                &text_events(&[(10, "Hel"), (10, "lo")]),
+            ],
+        );
+
+        assert_eq!(
+            midi.kar_lyrics(),
+            vec![(10, String::from("Hel")), (20, String::from("lo"))]
+        );
+    }
+
+    #[test]
+    fn new_file_has_no_tracks() {
+        let midi = MIDI::new(
+            Format::SingleMultiChannelTrack,
+            Division::TicksPerQuarterNote(480),
+        );
+
+        assert_eq!(midi.header().unwrap().tracks_count, 0);
+        assert_eq!(midi.tracks().count(), 0);
+    }
+
+    #[test]
+    fn pushed_track_is_declared() {
+        let mut midi = MIDI::new(
+            Format::SingleMultiChannelTrack,
+            Division::TicksPerQuarterNote(480),
+        );
+        let track_chunk = TrackChunk(vec![
+            TrackEvent {
+                delta_time: DeltaTicks(0),
+                kind: Event::Meta(MetaEvent::SetTempo(400_000)),
+            },
+            TrackEvent {
+                delta_time: DeltaTicks(0),
+                kind: Event::Meta(MetaEvent::EndOfTrack),
+            },
+        ]);
+
+        midi.push_track(track_chunk.clone());
+        let bytes = Vec::try_from(&midi).unwrap();
+
+        assert_eq!(&bytes[..14], b"MThd\0\0\0\x06\0\0\0\x01\x01\xE0");
+        assert_eq!(midi.tracks().collect::<Vec<_>>(), [&track_chunk]);
+        assert_eq!(MIDI::try_from(bytes).unwrap(), midi);
+    }
+
+    #[test]
+    fn pushed_empty_tracks_are_kept_and_promote_single_track_format() {
+        let mut midi = MIDI::new(
+            Format::SingleMultiChannelTrack,
+            Division::TicksPerQuarterNote(480),
+        );
+        let empty_track = TrackChunk(vec![TrackEvent {
+            delta_time: DeltaTicks(0),
+            kind: Event::Meta(MetaEvent::EndOfTrack),
+        }]);
+
+        midi.push_track(empty_track.clone());
+        assert_eq!(
+            midi.header().unwrap().format,
+            Format::SingleMultiChannelTrack
+        );
+        midi.push_track(empty_track.clone());
+
+        assert_eq!(midi.header().unwrap().format, Format::SimultaneousTracks);
+        assert_eq!(midi.header().unwrap().tracks_count, 2);
+        assert_eq!(
+            midi.tracks().collect::<Vec<_>>(),
+            [&empty_track, &empty_track]
+        );
+    }
+
+    #[test]
+    fn remove_track_decrements_tracks_count() {
+        let mut midi = midi(
+            1,
+            96,
+            &[
+                &[0x00, 0xFF, 0x2F, 0x00],
+                &[0x64, 0xFF, 0x2F, 0x00],
+                &[0x00, 0xFF, 0x2F, 0x00],
+            ],
+        );
+        let second = midi.tracks().nth(1).cloned();
+
+        assert_eq!(midi.remove_track(1), second);
+        assert_eq!(midi.header().unwrap().tracks_count, 2);
+        assert_eq!(midi.remove_track(2), None);
+        assert_eq!(midi.header().unwrap().tracks_count, 2);
+        assert_eq!(MIDI::try_from(Vec::try_from(&midi).unwrap()).unwrap(), midi);
+    }
+
+    #[test]
+    fn running_status_is_semantically_equal_to_explicit_status() {
+        let running = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0x90, 0x3C, 0x40, 0x10, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+        let explicit = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0x90, 0x3C, 0x40, 0x10, 0x90, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+        let without_end_of_track =
+            midi(0, 96, &[&[0x00, 0x90, 0x3C, 0x40, 0x10, 0x90, 0x3C, 0x00]]);
+        let later = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0x90, 0x3C, 0x40, 0x11, 0x90, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+
+        assert!(running.semantically_eq(&explicit));
+        assert!(running.semantically_eq(&without_end_of_track));
+        assert!(!running.semantically_eq(&later));
+        assert_ne!(running, without_end_of_track);
+    }
+
+    #[test]
+    fn truncated_second_track_keeps_header_and_first_track() {
+        let full = MIDI::try_from(LAPIS_LAZULI.to_vec()).unwrap();
+        let second = crate::core::chunk::chunk_infos(LAPIS_LAZULI).unwrap()[2];
+        let cut = second.offset + 8 + second.length as usize / 2;
+
+        let (chunks, error) = MIDI::try_from_partial(&MIDIFile::from(LAPIS_LAZULI[..cut].to_vec()));
+
+        assert_eq!(chunks.len(), 2);
+        assert!(matches!(chunks[0], Chunk::Header(_)));
+        assert_eq!(chunks[1], full[1]);
+        assert!(matches!(error, Some(TryFromError::MIDIFileToChunksFile(_))));
+    }
+
+    #[test]
+    fn corrupt_second_track_keeps_header_and_first_track() {
+        let second = crate::core::chunk::chunk_infos(LAPIS_LAZULI).unwrap()[2];
+        // A note on without any status to run from.
+        let mut bytes = LAPIS_LAZULI[..second.offset + 4].to_vec();
+        bytes.extend(3u32.to_be_bytes());
+        bytes.extend([0x00, 0x3C, 0x40]);
+
+        let (chunks, error) = MIDI::try_from_partial(&MIDIFile::from(bytes));
+
+        assert_eq!(chunks.len(), 2);
+        assert!(matches!(error, Some(TryFromError::ChunkFileToChunk(_))));
+    }
+
+    /// A C major chord, then C, then the first C again with velocity 0, and a
+    /// B, in a track of its own with a drum note.
+    fn chord_and_drum() -> MIDI {
+        midi(
+            1,
+            96,
+            &[
+                &[
+                    0x00, 0x90, 0x3C, 0x64, 0x00, 0x40, 0x64, 0x00, 0x43, 0x64, 0x0A, 0x48, 0x5A,
+                    0x0A, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+                ],
+                &[
+                    0x00, 0x99, 0x24, 0x64, 0x00, 0x91, 0x47, 0x01, 0x00, 0xFF, 0x2F, 0x00,
+                ],
+            ],
+        )
+    }
+
+    #[test]
+    fn pitch_class_histogram_counts_sounding_notes() {
+        let midi = chord_and_drum();
+
+        let histogram = midi.pitch_class_histogram(&AnalysisOptions::default());
+
+        assert_eq!(histogram, [3, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn excluding_drum_channel_removes_drum_notes_from_histogram() {
+        let midi = chord_and_drum();
+        let mut exclude_channels = ChannelSet::EMPTY;
+        exclude_channels.insert(DRUM_CHANNEL);
+
+        let histogram = midi.pitch_class_histogram(&AnalysisOptions { exclude_channels });
+
+        assert_eq!(histogram, [2, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn to_csv_writes_header_and_event_rows() {
+        let midi = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0xFF, 0x03, 0x0E, b'H', b'e', b'l', b'l', b'o', b',', b' ', b'"', b'w', b'o',
+                b'r', b'l', b'd', b'"', 0x00, 0x92, 0x3C, 0x64, 0x30, 0xB2, 0x07, 0x5A, 0x30, 0xE2,
+                0x00, 0x40, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+        let mut csv = Vec::new();
+
+        midi.to_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap().lines().collect::<Vec<_>>(),
+            [
+                "track,absolute_tick,event_type,channel,data1,data2,meta_type,text",
+                "0,0,Meta,,,,3,\"Hello, \"\"world\"\"\"",
+                "0,0,NoteOn,2,60,100,,",
+                "0,48,ControlChange,2,7,90,,",
+                "0,96,PitchBend,2,8192,,,",
+                "0,96,Meta,,,,47,",
+            ]
+        );
+    }
+
+    #[test]
+    fn merging_single_track_files_gives_two_track_format_1() {
+        let note = [
+            0x00, 0x90, 0x3C, 0x40, 0x60, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let mut merged = midi(0, 96, &[&note]);
+
+        merged.merge(&midi(0, 96, &[&note])).unwrap();
+
+        assert_eq!(merged.tracks().count(), 2);
+        assert_eq!(merged.header().unwrap().tracks_count, 2);
+        assert_eq!(merged.header().unwrap().format, Format::SimultaneousTracks);
+    }
+
+    #[test]
+    fn merge_rejects_incompatible_division() {
+        let note = [
+            0x00, 0x90, 0x3C, 0x40, 0x60, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let mut merged = midi(0, 96, &[&note]);
+        let before = merged.clone();
+
+        assert!(matches!(
+            merged.merge(&midi(0, 480, &[&note])),
+            Err(MergeError::IncompatibleDivision)
+        ));
+        assert_eq!(merged, before);
+    }
+
+    /// The absolute ticks of the events of every track of `midi`.
+    fn ticks(midi: &MIDI) -> Vec<Vec<u64>> {
+        midi.tracks()
+            .map(|track_chunk| {
+                track_chunk
+                    .absolute_events()
+                    .map(|(AbsoluteTicks(tick), _)| tick)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn append_sequentially_starts_other_at_end_tick() {
+        let mut first = midi(
+            1,
+            96,
+            &[
+                &[
+                    0x00, 0x90, 0x3C, 0x40, 0x64, 0x3D, 0x40, 0x00, 0xFF, 0x2F, 0x00,
+                ],
+                &[
+                    0x00, 0x90, 0x28, 0x40, 0x82, 0x2C, 0x29, 0x40, 0x00, 0xFF, 0x2F, 0x00,
+                ],
+            ],
+        );
+        let second = midi(
+            1,
+            96,
+            &[
+                &[
+                    0x0A, 0x90, 0x46, 0x40, 0x0A, 0x47, 0x40, 0x00, 0xFF, 0x2F, 0x00,
+                ],
+                &[0x05, 0x90, 0x32, 0x40, 0x00, 0xFF, 0x2F, 0x00],
+                &[0x07, 0x90, 0x1E, 0x40, 0x00, 0xFF, 0x2F, 0x00],
+            ],
+        );
+        assert_eq!(first.duration_ticks(), 300);
+
+        first.append_sequentially(&second).unwrap();
+
+        assert_eq!(
+            ticks(&first),
+            [
+                vec![0, 100, 310, 320, 320],
+                vec![0, 300, 305, 305],
+                vec![307, 307]
+            ]
+        );
+        assert_eq!(first.header().unwrap().tracks_count, 3);
+    }
+
+    #[test]
+    fn append_sequentially_keeps_late_end_of_track() {
+        let mut first = midi(1, 96, &[&[0x00, 0x90, 0x3C, 0x40, 0x60, 0xFF, 0x2F, 0x00]]);
+        let second = midi(1, 96, &[&[0x00, 0x90, 0x3E, 0x40, 0x00, 0xFF, 0x2F, 0x00]]);
+
+        first.append_sequentially(&second).unwrap();
+
+        assert_eq!(ticks(&first), [vec![0, 96, 96]]);
+    }
+
+    #[test]
+    fn append_sequentially_promotes_single_track_format() {
+        let end_of_track: &[u8] = &[0x60, 0xFF, 0x2F, 0x00];
+        let mut first = midi(0, 96, &[end_of_track]);
+        let second = midi(1, 96, &[end_of_track, end_of_track]);
+
+        first.append_sequentially(&second).unwrap();
+
+        assert_eq!(first.header().unwrap().format, Format::SimultaneousTracks);
+        assert_eq!(first.header().unwrap().tracks_count, 2);
+    }
+
+    #[test]
+    fn append_sequentially_rejects_delta_time_overflow() {
+        // An `EndOfTrack` at `DeltaTicks::MAX`.
+        let long: &[u8] = &[0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0x2F, 0x00];
+        let mut first = midi(1, 96, &[long, long]);
+        let second = midi(
+            1,
+            96,
+            &[&[0x00, 0xFF, 0x2F, 0x00], &[0x01, 0xFF, 0x2F, 0x00]],
+        );
+        let before = first.clone();
+
+        assert!(matches!(
+            first.append_sequentially(&second),
+            Err(MergeError::DeltaTimeOverflow)
+        ));
+        assert_eq!(first, before);
+    }
+
+    #[test]
+    fn doubling_tempo_halves_bpm() {
+        let bpm = |tempo: u32| 60_000_000.0 / f64::from(tempo);
+        // 120 BPM, then 150 BPM.
+        let mut midi = midi(
+            1,
+            96,
+            &[&[
+                0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, 0x60, 0xFF, 0x51, 0x03, 0x06, 0x1A, 0x80,
+                0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+
+        midi.scale_tempo(2.0);
+
+        let tempo_map = TempoMap::from(&midi);
+        assert_eq!(bpm(tempo_map.tempo_at(0)), 60.0);
+        assert_eq!(bpm(tempo_map.tempo_at(96)), 75.0);
+    }
+
+    #[test]
+    fn scale_tempo_ignores_invalid_factors() {
+        let mut midi = midi(
+            1,
+            96,
+            &[&[
+                0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+        let before = midi.clone();
+
+        for factor in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            midi.scale_tempo(factor);
+        }
+
+        assert_eq!(midi, before);
+    }
+
+    #[test]
+    fn running_status_and_explicit_status_have_same_fingerprint() {
+        let explicit = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0x90, 0x3C, 0x40, 0x10, 0x90, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+        let running = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0x90, 0x3C, 0x40, 0x10, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+        let later = midi(
+            0,
+            96,
+            &[&[
+                0x00, 0x90, 0x3C, 0x40, 0x11, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]],
+        );
+
+        assert_eq!(
+            explicit.content_fingerprint(),
+            running.content_fingerprint()
+        );
+        assert_ne!(explicit.content_fingerprint(), later.content_fingerprint());
+    }
+
+    #[test]
+    fn encoding_rejects_delta_time_above_max() {
+        let mut midi = MIDI::new(
+            Format::SimultaneousTracks,
+            Division::TicksPerQuarterNote(96),
+        );
+        midi.push_track(TrackChunk(vec![TrackEvent {
+            delta_time: DeltaTicks(u32::MAX),
+            kind: Event::Meta(MetaEvent::EndOfTrack),
+        }]));
+
+        assert!(matches!(
+            Vec::try_from(&midi),
+            Err(EncodeError::ChunkToBytes(
+                crate::core::chunk::EncodeError::TrackChunkToBytes(
+                    crate::core::chunk::track::EncodeError::DeltaTimeOverflow
+                )
+            ))
+        ));
+    }
 }
@@ -1,3 +1,7 @@
 pub mod chunk;
 pub mod event;
 pub mod midi;
+pub mod summary;
+pub mod tempo;
+pub mod ticks;
+pub mod time_signature;
@@ -0,0 +1,118 @@
+use ::core::time::Duration;
+use alloc::{string::String, vec::Vec};
+
+use derive_more::Debug;
+
+use crate::core::{
+    chunk::{
+        header::{division::Division, format::Format},
+        track::TrackChunk,
+    },
+    event::{Event, channel::ChannelMessage, meta::MetaEvent},
+};
+
+/// A one-call overview of a [`MIDI`](crate::core::midi::MIDI), see
+/// [`MIDI::summary`](crate::core::midi::MIDI::summary).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MIDISummary {
+    /// The format of the header, if any.
+    pub format: Option<Format>,
+
+    /// The number of track chunks actually present, which may differ from
+    /// the count declared by the header.
+    pub tracks_count: usize,
+
+    /// The division of the header, if any.
+    pub division: Option<Division>,
+
+    /// The playback length, if known, see
+    /// [`MIDI::duration`](crate::core::midi::MIDI::duration).
+    pub duration: Option<Duration>,
+
+    /// A [`TrackSummary`] of every track chunk, in file order.
+    pub tracks: Vec<TrackSummary>,
+}
+
+/// An overview of a [`TrackChunk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TrackSummary {
+    /// The text of the first [`MetaEvent::SequenceOrTrackName`], if any.
+    pub name: Option<String>,
+
+    /// The number of `NoteOn` and `NoteOff` events.
+    pub note_events: usize,
+
+    /// The number of meta events.
+    pub meta_events: usize,
+
+    /// The number of system exclusive events.
+    pub sys_ex_events: usize,
+}
+
+impl From<&TrackChunk> for TrackSummary {
+    fn from(value: &TrackChunk) -> Self {
+        let mut track_summary = TrackSummary {
+            name: None,
+            note_events: 0,
+            meta_events: 0,
+            sys_ex_events: 0,
+        };
+        for track_event in value.iter() {
+            match &track_event.kind {
+                Event::Channel(channel_event) => {
+                    if let ChannelMessage::NoteOff { .. } | ChannelMessage::NoteOn { .. } =
+                        channel_event.message
+                    {
+                        track_summary.note_events += 1;
+                    }
+                }
+                Event::Meta(meta_event) => {
+                    if let (None, MetaEvent::SequenceOrTrackName(text)) =
+                        (&track_summary.name, meta_event)
+                    {
+                        track_summary.name = Some(text.text.clone());
+                    }
+                    track_summary.meta_events += 1;
+                }
+                Event::SysEx(_) => track_summary.sys_ex_events += 1,
+            }
+        }
+        track_summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::midi::MIDI;
+
+    #[test]
+    fn summary_of_bundled_asset() {
+        let bytes = include_bytes!("../../../../assets/World Vanquisher.mid");
+        let midi = MIDI::try_from(bytes.to_vec()).unwrap();
+
+        let summary = midi.summary();
+
+        assert_eq!(summary.format, Some(midi.header().unwrap().format));
+        assert_eq!(
+            summary.division,
+            Some(midi.header().unwrap().division.clone())
+        );
+        assert_eq!(summary.tracks_count, midi.tracks().count());
+        assert_eq!(
+            summary.tracks,
+            midi.tracks().map(TrackSummary::from).collect::<Vec<_>>()
+        );
+        assert!(summary.duration.is_some());
+        assert!(
+            summary
+                .tracks
+                .iter()
+                .map(|track_summary| track_summary.note_events)
+                .sum::<usize>()
+                > 0
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use ::core::time::Duration;
+use alloc::vec::Vec;
+
+use derive_more::{Debug, Deref};
+
+use crate::core::{
+    chunk::{header::division::Division, track::TrackChunk},
+    event::{Event, meta::MetaEvent},
+    midi::MIDI,
+    ticks::AbsoluteTicks,
+};
+
+/// The tempo assumed before the first [`MetaEvent::SetTempo`], in
+/// microseconds per quarter-note: 120 beats per minute.
+pub const DEFAULT_TEMPO: u32 = 500_000;
+
+/// The tempo changes of a [`MIDI`] as `(absolute_tick, tempo)`, in time order,
+/// where `tempo` is in microseconds per quarter-note.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deref)]
+pub struct TempoMap(Vec<(u64, u32)>);
+
+impl From<&MIDI> for TempoMap {
+    /// Collects every [`MetaEvent::SetTempo`] of every track of `value`.
+    ///
+    /// Simultaneous tempo changes keep their track order, so the last one
+    /// wins.
+    fn from(value: &MIDI) -> Self {
+        TempoMap::from_meta_events(value.meta_events())
+    }
+}
+
+impl From<&TrackChunk> for TempoMap {
+    /// Collects every [`MetaEvent::SetTempo`] of `value`, such as the one
+    /// returned by [`MIDI::tempo_track`].
+    fn from(value: &TrackChunk) -> Self {
+        TempoMap::from_meta_events(value.absolute_events().filter_map(
+            |(AbsoluteTicks(tick), track_event)| match &track_event.kind {
+                Event::Meta(meta_event) => Some((tick, meta_event)),
+                _ => None,
+            },
+        ))
+    }
+}
+
+impl TempoMap {
+    /// Collects the tempo changes among `meta_events`, sorted by tick.
+    fn from_meta_events<'a>(meta_events: impl Iterator<Item = (u64, &'a MetaEvent)>) -> Self {
+        let mut tempos: Vec<(u64, u32)> = meta_events
+            .filter_map(|(tick, meta_event)| match meta_event {
+                MetaEvent::SetTempo(tempo) => Some((tick, *tempo)),
+                _ => None,
+            })
+            .collect();
+        tempos.sort_by_key(|(tick, _)| *tick);
+        TempoMap(tempos)
+    }
+
+    /// The tempo in effect at the absolute `tick`, in microseconds per
+    /// quarter-note.
+    pub fn tempo_at(&self, tick: u64) -> u32 {
+        self.iter()
+            .take_while(|(change_tick, _)| *change_tick <= tick)
+            .last()
+            .map_or(DEFAULT_TEMPO, |(_, tempo)| *tempo)
+    }
+
+    /// The number of seconds elapsed at the absolute `tick` of a track.
+    ///
+    /// For a metrical `division`, every tick lasts the tempo in effect divided
+    /// by the ticks per quarter-note. A time-code-based `division` does not
+    /// depend on tempo, see [`Division::smpte_seconds_at`].
+    pub fn seconds_at(&self, tick: u64, division: &Division) -> f64 {
+        match division {
+            Division::TicksPerQuarterNote(ticks_per_quarter) => {
+                self.tempo_ticks_at(tick) as f64 / (f64::from(*ticks_per_quarter) * 1_000_000.0)
+            }
+            Division::TimeCode { .. } => division.smpte_seconds_at(tick).unwrap_or_default(),
+        }
+    }
+
+    /// The time elapsed at the absolute `tick` of a track, like
+    /// [`TempoMap::seconds_at`] but without floating-point rounding for a
+    /// metrical `division`.
+    ///
+    /// Returns `None` if `division` has zero ticks per quarter-note.
+    pub fn duration_at(&self, tick: u64, division: &Division) -> Option<Duration> {
+        match division {
+            Division::TicksPerQuarterNote(0) => None,
+            Division::TicksPerQuarterNote(ticks_per_quarter) => {
+                let nanoseconds =
+                    self.tempo_ticks_at(tick) * 1_000 / u128::from(*ticks_per_quarter);
+                Some(Duration::from_nanos(
+                    u64::try_from(nanoseconds).unwrap_or(u64::MAX),
+                ))
+            }
+            Division::TimeCode { .. } => {
+                Duration::try_from_secs_f64(division.smpte_seconds_at(tick)?).ok()
+            }
+        }
+    }
+
+    /// The sum of the tempo of every tick up to the absolute `tick`, in
+    /// microseconds per quarter-note. Dividing it by the ticks per
+    /// quarter-note gives the elapsed microseconds.
+    fn tempo_ticks_at(&self, tick: u64) -> u128 {
+        let mut tempo_ticks: u128 = 0;
+        let mut segment_tick: u64 = 0;
+        let mut segment_tempo = DEFAULT_TEMPO;
+        for (change_tick, tempo) in self.iter() {
+            if *change_tick >= tick {
+                break;
+            }
+            tempo_ticks += u128::from(change_tick - segment_tick) * u128::from(segment_tempo);
+            segment_tick = *change_tick;
+            segment_tempo = *tempo;
+        }
+        tempo_ticks + u128::from(tick - segment_tick) * u128::from(segment_tempo)
+    }
+}
@@ -0,0 +1,71 @@
+//! A module distinguishing delta-times from absolute times in the type
+//! system, as both are counted in ticks.
+
+use ::core::ops::{Add, AddAssign};
+
+use derive_more::{Debug, Display, From, Into};
+
+/// A delta-time: the number of ticks between an event and the previous event
+/// of its track, or the start of the track for the first one.
+#[derive(
+    Debug, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, From, Into,
+)]
+pub struct DeltaTicks(pub u32);
+
+impl DeltaTicks {
+    /// The largest delta-time, as a variable-length quantity has at most 4
+    /// bytes of 7 bits each.
+    pub const MAX: DeltaTicks = DeltaTicks(0x0FFF_FFFF);
+
+    /// Creates a new [`DeltaTicks`] of `ticks`, or returns `None` if it is
+    /// larger than [`DeltaTicks::MAX`].
+    pub fn new(ticks: u64) -> Option<Self> {
+        match u32::try_from(ticks) {
+            Ok(ticks) if ticks <= DeltaTicks::MAX.0 => Some(DeltaTicks(ticks)),
+            _ => None,
+        }
+    }
+}
+
+/// An absolute time: the number of ticks since the start of a track, that is,
+/// the sum of the delta-times of every event up to and including the current
+/// one.
+#[derive(
+    Debug, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, From, Into,
+)]
+pub struct AbsoluteTicks(pub u64);
+
+impl Add<DeltaTicks> for AbsoluteTicks {
+    type Output = AbsoluteTicks;
+
+    /// The absolute time of an event `rhs` ticks after `self`.
+    fn add(self, rhs: DeltaTicks) -> Self::Output {
+        AbsoluteTicks(self.0 + u64::from(rhs.0))
+    }
+}
+
+impl AddAssign<DeltaTicks> for AbsoluteTicks {
+    fn add_assign(&mut self, rhs: DeltaTicks) {
+        *self = *self + rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_plus_delta_is_absolute() {
+        let mut tick = AbsoluteTicks(100);
+
+        assert_eq!(tick + DeltaTicks(20), AbsoluteTicks(120));
+        tick += DeltaTicks::MAX;
+        assert_eq!(tick, AbsoluteTicks(100 + 0x0FFF_FFFF));
+    }
+
+    #[test]
+    fn new_rejects_delta_above_max() {
+        assert_eq!(DeltaTicks::new(0x0FFF_FFFF), Some(DeltaTicks::MAX));
+        assert_eq!(DeltaTicks::new(0x1000_0000), None);
+    }
+}
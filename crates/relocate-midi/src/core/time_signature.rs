@@ -0,0 +1,185 @@
+use alloc::vec::Vec;
+
+use derive_more::{Debug, Deref};
+
+use crate::core::{
+    chunk::track::TrackChunk,
+    event::{Event, meta::MetaEvent},
+    midi::MIDI,
+    ticks::AbsoluteTicks,
+};
+
+/// The time signature assumed before the first [`MetaEvent::TimeSignature`],
+/// as `(numerator, denominator)` where the denominator is a negative power of
+/// two: 4/4.
+pub const DEFAULT_TIME_SIGNATURE: (u8, u8) = (4, 2);
+
+/// The time signature changes of a [`MIDI`] as
+/// `(absolute_tick, numerator, denominator)`, in time order, where the
+/// denominator is a negative power of two as in
+/// [`MetaEvent::TimeSignature`].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deref)]
+pub struct TimeSignatureMap(Vec<(u64, u8, u8)>);
+
+impl From<&MIDI> for TimeSignatureMap {
+    /// Collects every [`MetaEvent::TimeSignature`] of every track of `value`.
+    ///
+    /// Simultaneous changes keep their track order, so the last one wins.
+    fn from(value: &MIDI) -> Self {
+        TimeSignatureMap::from_meta_events(value.meta_events())
+    }
+}
+
+impl From<&TrackChunk> for TimeSignatureMap {
+    /// Collects every [`MetaEvent::TimeSignature`] of `value`, such as the
+    /// one returned by [`MIDI::tempo_track`].
+    fn from(value: &TrackChunk) -> Self {
+        TimeSignatureMap::from_meta_events(value.absolute_events().filter_map(
+            |(AbsoluteTicks(tick), track_event)| match &track_event.kind {
+                Event::Meta(meta_event) => Some((tick, meta_event)),
+                _ => None,
+            },
+        ))
+    }
+}
+
+impl TimeSignatureMap {
+    /// Collects the time signature changes among `meta_events`, sorted by
+    /// tick.
+    fn from_meta_events<'a>(meta_events: impl Iterator<Item = (u64, &'a MetaEvent)>) -> Self {
+        let mut time_signatures: Vec<(u64, u8, u8)> = meta_events
+            .filter_map(|(tick, meta_event)| match meta_event {
+                MetaEvent::TimeSignature {
+                    numerator,
+                    denominator,
+                    ..
+                } => Some((tick, *numerator, *denominator)),
+                _ => None,
+            })
+            .collect();
+        time_signatures.sort_by_key(|(tick, _, _)| *tick);
+        TimeSignatureMap(time_signatures)
+    }
+
+    /// The time signature in effect at the absolute `tick`, as
+    /// `(numerator, denominator)` where the denominator is the note value of a
+    /// beat, such as `(3, 4)` for 3/4.
+    ///
+    /// A denominator too large for a `u32` saturates to `u32::MAX`.
+    pub fn signature_at(&self, tick: u64) -> (u8, u32) {
+        let (numerator, denominator) = self
+            .iter()
+            .take_while(|(change_tick, _, _)| *change_tick <= tick)
+            .last()
+            .map_or(DEFAULT_TIME_SIGNATURE, |(_, numerator, denominator)| {
+                (*numerator, *denominator)
+            });
+        (
+            numerator,
+            1u32.checked_shl(u32::from(denominator)).unwrap_or(u32::MAX),
+        )
+    }
+}
+
+/// A position in musical terms, as shown by notation software.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MusicalTime {
+    /// The bar, counted from 1.
+    pub bar: u64,
+
+    /// The beat within the bar, counted from 1, where a beat is one
+    /// denominator note of the time signature.
+    pub beat: u64,
+
+    /// The ticks elapsed since the start of the beat.
+    pub tick: u64,
+}
+
+impl MusicalTime {
+    /// The [`MusicalTime`] of the absolute `tick`, following the time
+    /// signature changes of `time_signature_map` in a metrical division of
+    /// `ticks_per_quarter` ticks per quarter-note.
+    ///
+    /// A time signature change always starts a new bar, even if the previous
+    /// bar is incomplete. Returns `None` if a time signature in effect has a
+    /// zero numerator, or a beat shorter than a tick.
+    pub fn at(
+        tick: u64,
+        time_signature_map: &TimeSignatureMap,
+        ticks_per_quarter: u16,
+    ) -> Option<Self> {
+        let mut bar: u64 = 1;
+        let mut segment_tick: u64 = 0;
+        let (mut numerator, mut denominator) = DEFAULT_TIME_SIGNATURE;
+        for (change_tick, change_numerator, change_denominator) in time_signature_map.iter() {
+            if *change_tick > tick {
+                break;
+            }
+            let bar_ticks = bar_ticks(numerator, denominator, ticks_per_quarter)?;
+            bar += (change_tick - segment_tick).div_ceil(bar_ticks);
+            segment_tick = *change_tick;
+            (numerator, denominator) = (*change_numerator, *change_denominator);
+        }
+
+        let beat_ticks = beat_ticks(denominator, ticks_per_quarter)?;
+        let bar_ticks = bar_ticks(numerator, denominator, ticks_per_quarter)?;
+        let elapsed = tick - segment_tick;
+        Some(MusicalTime {
+            bar: bar + elapsed / bar_ticks,
+            beat: elapsed % bar_ticks / beat_ticks + 1,
+            tick: elapsed % beat_ticks,
+        })
+    }
+}
+
+/// The ticks in a beat of a time signature `denominator`.
+fn beat_ticks(denominator: u8, ticks_per_quarter: u16) -> Option<u64> {
+    (u64::from(ticks_per_quarter) * 4)
+        .checked_shr(u32::from(denominator))
+        .filter(|beat_ticks| *beat_ticks > 0)
+}
+
+/// The ticks in a bar of a time signature.
+fn bar_ticks(numerator: u8, denominator: u8, ticks_per_quarter: u16) -> Option<u64> {
+    let bar_ticks = beat_ticks(denominator, ticks_per_quarter)? * u64::from(numerator);
+    (bar_ticks > 0).then_some(bar_ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn bar_2_starts_at_tick_1920_in_4_4() {
+        let time_signature_map = TimeSignatureMap::default();
+
+        assert_eq!(
+            MusicalTime::at(1920, &time_signature_map, 480),
+            Some(MusicalTime {
+                bar: 2,
+                beat: 1,
+                tick: 0
+            })
+        );
+        assert_eq!(
+            MusicalTime::at(1919, &time_signature_map, 480),
+            Some(MusicalTime {
+                bar: 1,
+                beat: 4,
+                tick: 479
+            })
+        );
+    }
+
+    #[test]
+    fn signature_switches_from_4_4_to_3_4() {
+        let time_signature_map = TimeSignatureMap(vec![(0, 4, 2), (3840, 3, 2)]);
+
+        assert_eq!(TimeSignatureMap::default().signature_at(100), (4, 4));
+        assert_eq!(time_signature_map.signature_at(3839), (4, 4));
+        assert_eq!(time_signature_map.signature_at(3840), (3, 4));
+        assert_eq!(time_signature_map.signature_at(99_999), (3, 4));
+    }
+}
@@ -0,0 +1,48 @@
+//! A module joining the errors of reading and parsing a file, so that
+//! applications can handle both with a single `?`.
+
+use std::{fs, io, path::Path};
+
+use derive_more::{Debug, Display, Error, From};
+
+use crate::core::midi::{MIDI, TryFromError};
+
+#[derive(Debug, Display, Error, From)]
+pub enum Error {
+    /// The file could not be read.
+    Io(io::Error),
+
+    /// The file was read but is not a valid [`MIDI`].
+    Parse(TryFromError),
+}
+
+/// Reads the file at `path` and parses it into a [`MIDI`], like
+/// `MIDI::try_from(fs::read(path)?)`.
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<MIDI, Error> {
+    let bytes = fs::read(path)?;
+    Ok(MIDI::try_from(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_file_of_nonexistent_path_is_io_error() {
+        assert!(matches!(
+            parse_file("/nonexistent/relocate.mid"),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn parse_file_of_bundled_asset() {
+        assert!(
+            parse_file(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../assets/World Vanquisher.mid"
+            ))
+            .is_ok()
+        );
+    }
+}
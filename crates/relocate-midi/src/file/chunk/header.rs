@@ -71,3 +71,30 @@ impl<'a> TryFrom<&ChunkFile<'a>> for HeaderChunkFile<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::chunk::track::TRACK_CHUNK_KIND;
+
+    fn chunk_file(kind: &'static [u8; 4]) -> ChunkFile<'static> {
+        ChunkFile {
+            kind,
+            length: 6,
+            offset: 0,
+            data: &[0x00, 0x01, 0x00, 0x02, 0x01, 0xE0],
+        }
+    }
+
+    #[test]
+    fn only_header_chunk_kind_is_a_header() {
+        let header_chunk_file = HeaderChunkFile::try_from(&chunk_file(HEADER_CHUNK_KIND)).unwrap();
+
+        assert_eq!(header_chunk_file.kind(), b"MThd");
+        assert_eq!(header_chunk_file.format, &[0x00, 0x01]);
+        assert!(matches!(
+            HeaderChunkFile::try_from(&chunk_file(TRACK_CHUNK_KIND)),
+            Err(TryFromError::InvalidKind)
+        ));
+    }
+}
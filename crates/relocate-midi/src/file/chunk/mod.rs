@@ -1,50 +1,397 @@
 pub mod header;
 pub mod track;
 
+use alloc::{string::String, vec::Vec};
+
 use derive_more::{Debug, Deref, Display, Error, IntoIterator};
+use log::{trace, warn};
 
-use crate::{file::midi::MIDIFile, scanner::Scanner};
+use crate::{
+    file::{
+        chunk::{
+            header::{HEADER_CHUNK_KIND, HeaderChunkFile},
+            track::{TRACK_CHUNK_KIND, TrackChunkFile},
+        },
+        midi::MIDIFile,
+    },
+    options::ParseOptions,
+    scanner::Scanner,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct ChunkFile<'a> {
     #[debug("{:?}", String::from_utf8_lossy(*kind))]
     pub kind: &'a [u8; 4],
     pub length: u32,
+
+    /// The index of the first byte of the chunk, its kind, in the file.
+    pub offset: usize,
     #[debug(skip)]
     pub data: &'a [u8],
 }
 
+/// The length of the kind and length of a chunk, before its data.
+const CHUNK_PREFIX_LENGTH: usize = 8;
+
 #[derive(Debug, Deref, IntoIterator)]
-pub struct ChunksFile<'a>(Vec<ChunkFile<'a>>);
+pub struct ChunksFile<'a> {
+    #[deref]
+    #[into_iterator(owned)]
+    chunks: Vec<ChunkFile<'a>>,
+
+    /// The bytes after the last chunk, too few to be the kind and length of
+    /// another one, such as junk appended to the file. Empty if the chunks
+    /// span the whole file.
+    ///
+    /// From `ChunksFile::try_from_partial` along with an error, the bytes of
+    /// the chunk that could not be read and after it instead, unless no chunk
+    /// was read at all.
+    pub trailing: &'a [u8],
+}
+
+/// A [`ChunkFile`] viewed as the chunk its kind says it is, still borrowing
+/// the bytes of the file, in between a [`ChunkFile`] and an owned
+/// [`Chunk`](crate::core::chunk::Chunk).
+#[derive(Debug)]
+pub enum TypedChunkFile<'a> {
+    Header(HeaderChunkFile<'a>),
+    Track(TrackChunkFile<'a>),
+
+    /// A chunk of any other kind, which should be ignored.
+    Alien(ChunkFile<'a>),
+}
+
+#[derive(Debug, Display, Error)]
+pub enum TypedError {
+    ChunkFileToHeaderChunkFile(header::TryFromError),
+    ChunkFileToTrackChunkFile(track::TryFromError),
+}
+
+impl<'a> TryFrom<&ChunkFile<'a>> for TypedChunkFile<'a> {
+    type Error = TypedError;
+
+    fn try_from(value: &ChunkFile<'a>) -> Result<Self, Self::Error> {
+        match value.kind {
+            HEADER_CHUNK_KIND => HeaderChunkFile::try_from(value)
+                .map(TypedChunkFile::Header)
+                .map_err(TypedError::ChunkFileToHeaderChunkFile),
+            TRACK_CHUNK_KIND => TrackChunkFile::try_from(value)
+                .map(TypedChunkFile::Track)
+                .map_err(TypedError::ChunkFileToTrackChunkFile),
+            _ => Ok(TypedChunkFile::Alien(*value)),
+        }
+    }
+}
 
 #[derive(Debug, Display, Error)]
 pub enum TryFromError {
     CouldNotReadKind,
     CouldNotReadLength,
     CouldNotReadData,
+    #[display("LengthExceedsBuffer({declared}, {available})")]
+    LengthExceedsBuffer {
+        declared: u32,
+        available: usize,
+    },
 }
 
 impl<'a> TryFrom<&'a MIDIFile> for ChunksFile<'a> {
     type Error = TryFromError;
 
     fn try_from(value: &'a MIDIFile) -> Result<Self, Self::Error> {
+        ChunksFile::try_from_with_options(value, &ParseOptions::default())
+    }
+}
+
+impl<'a> ChunksFile<'a> {
+    /// Converts the bytes of a [`MIDIFile`] like `ChunksFile::try_from`,
+    /// following `options`.
+    ///
+    /// With [`ParseOptions::recover_chunk_lengths`], a chunk whose declared
+    /// length runs past the end of the file ends right before the next
+    /// `MThd` or `MTrk` magic, or at the end of the file if there is none. Its
+    /// `length` stays the declared one, longer than its recovered `data`.
+    ///
+    /// Fewer than 8 bytes after the last chunk are not an error, but kept as
+    /// [`ChunksFile::trailing`].
+    pub fn try_from_with_options(
+        value: &'a [u8],
+        options: &ParseOptions,
+    ) -> Result<Self, TryFromError> {
+        match ChunksFile::try_from_partial(value, options) {
+            (chunks_file, None) => Ok(chunks_file),
+            (_, Some(error)) => Err(error),
+        }
+    }
+
+    /// Reads the chunks of `value` like `ChunksFile::try_from_with_options`,
+    /// but stops at the first chunk that cannot be read, returning the
+    /// chunks read before it along with the error, if any.
+    ///
+    /// The bytes from that chunk on are kept as [`ChunksFile::trailing`] if
+    /// a chunk was read before it, so that they can be inspected.
+    pub fn try_from_partial(
+        value: &'a [u8],
+        options: &ParseOptions,
+    ) -> (Self, Option<TryFromError>) {
         let mut files = Vec::new();
         let mut scanner = Scanner::new(value);
+        let mut trailing: &[u8] = &[];
 
         while !scanner.done() {
-            let kind = scanner
-                .eat_bytes::<4>()
-                .ok_or(TryFromError::CouldNotReadKind)?;
-            let length = scanner
-                .eat_u32_be()
-                .ok_or(TryFromError::CouldNotReadLength)?;
-            let data = scanner
-                .eat_slice(length as usize)
-                .ok_or(TryFromError::CouldNotReadData)?;
-
-            files.push(ChunkFile { kind, length, data });
+            let offset = scanner.cursor();
+            // Too few bytes to start another chunk are junk appended to the
+            // file rather than a truncated chunk, unless there is no chunk.
+            if !files.is_empty() && scanner.rest().len() < CHUNK_PREFIX_LENGTH {
+                trailing = scanner.rest();
+                break;
+            }
+            let unread = &value[offset..];
+            let Some(kind) = scanner.eat_bytes::<4>() else {
+                return (
+                    ChunksFile::unfinished(files, unread),
+                    Some(TryFromError::CouldNotReadKind),
+                );
+            };
+            let Some(length) = scanner.eat_u32_be() else {
+                return (
+                    ChunksFile::unfinished(files, unread),
+                    Some(TryFromError::CouldNotReadLength),
+                );
+            };
+            // The declared length is checked against the remaining bytes up
+            // front, in `u64`, rather than left to `eat_slice`, so that a huge
+            // one is reported as such whatever the width of `usize`.
+            let available = scanner.rest().len();
+            let declared = if u64::from(length) <= available as u64 {
+                length as usize
+            } else if options.recover_chunk_lengths {
+                let rest = scanner.rest();
+                let recovered = rest
+                    .windows(4)
+                    .position(|magic| magic == HEADER_CHUNK_KIND || magic == TRACK_CHUNK_KIND)
+                    .unwrap_or(rest.len());
+                warn!(
+                    "Chunk {:?} declares {} bytes but only {} could be recovered.",
+                    String::from_utf8_lossy(kind),
+                    length,
+                    recovered
+                );
+                recovered
+            } else {
+                return (
+                    ChunksFile::unfinished(files, unread),
+                    Some(TryFromError::LengthExceedsBuffer {
+                        declared: length,
+                        available,
+                    }),
+                );
+            };
+            let Some(data) = scanner.eat_slice(declared) else {
+                return (
+                    ChunksFile::unfinished(files, unread),
+                    Some(TryFromError::CouldNotReadData),
+                );
+            };
+
+            trace!(
+                "Read chunk {:?} of {} bytes at offset {}.",
+                String::from_utf8_lossy(kind),
+                data.len(),
+                offset
+            );
+            files.push(ChunkFile {
+                kind,
+                length,
+                offset,
+                data,
+            });
         }
 
-        Ok(ChunksFile(files))
+        (
+            ChunksFile {
+                chunks: files,
+                trailing,
+            },
+            None,
+        )
+    }
+
+    /// Iterates over the chunks of this [`ChunksFile`] as
+    /// [`TypedChunkFile`]s, without copying or decoding their data, such as
+    /// to only read the header of a file.
+    #[cfg(feature = "file")]
+    pub fn typed(&self) -> impl Iterator<Item = Result<TypedChunkFile<'a>, TypedError>> {
+        self.iter().map(TypedChunkFile::try_from)
+    }
+
+    /// The [`ChunksFile`] of `chunks` read before the `unread` bytes, which
+    /// are only kept if there is a chunk they could have been appended to.
+    fn unfinished(chunks: Vec<ChunkFile<'a>>, unread: &'a [u8]) -> Self {
+        let trailing = if chunks.is_empty() { &[] } else { unread };
+        ChunksFile { chunks, trailing }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    const HEADER: &[u8] = b"MThd\0\0\0\x06\0\x01\0\x02\0\x60";
+
+    /// The bytes of a chunk of `kind` declaring `length` bytes of `data`.
+    fn chunk(kind: &[u8; 4], length: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = kind.to_vec();
+        bytes.extend(length.to_be_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn too_long_length_is_recovered_at_next_chunk() {
+        let end_of_track = [0x00, 0xFF, 0x2F, 0x00];
+        let bytes = [
+            HEADER,
+            &chunk(TRACK_CHUNK_KIND, 100, &end_of_track),
+            &chunk(TRACK_CHUNK_KIND, 200, &end_of_track),
+        ]
+        .concat();
+
+        assert!(matches!(
+            ChunksFile::try_from_with_options(&bytes, &ParseOptions::default()),
+            Err(TryFromError::LengthExceedsBuffer {
+                declared: 100,
+                available: 16,
+            })
+        ));
+
+        let options = ParseOptions {
+            recover_chunk_lengths: true,
+            ..ParseOptions::default()
+        };
+        let chunks_file = ChunksFile::try_from_with_options(&bytes, &options).unwrap();
+        let data: Vec<_> = chunks_file
+            .iter()
+            .map(|chunk_file| chunk_file.data)
+            .collect();
+        assert_eq!(data, vec![&HEADER[8..], &end_of_track, &end_of_track]);
+        let lengths: Vec<_> = chunks_file
+            .iter()
+            .map(|chunk_file| chunk_file.length)
+            .collect();
+        assert_eq!(lengths, vec![6, 100, 200]);
+    }
+
+    #[test]
+    fn header_starts_at_0_and_first_track_after_it() {
+        let bytes = include_bytes!("../../../../../assets/World Vanquisher.mid");
+
+        let chunks_file =
+            ChunksFile::try_from_with_options(bytes, &ParseOptions::default()).unwrap();
+
+        assert_eq!(chunks_file[0].offset, 0);
+        assert_eq!(chunks_file[1].offset, 14);
+        for pair in chunks_file.windows(2) {
+            assert_eq!(
+                pair[1].offset,
+                pair[0].offset + CHUNK_PREFIX_LENGTH + pair[0].length as usize
+            );
+        }
+    }
+
+    #[test]
+    fn multi_gigabyte_length_exceeds_buffer() {
+        let bytes = chunk(TRACK_CHUNK_KIND, 0xFFFF_FFF0, &[1, 2, 3]);
+
+        let error =
+            ChunksFile::try_from_with_options(&bytes, &ParseOptions::default()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            TryFromError::LengthExceedsBuffer {
+                declared: 0xFFFF_FFF0,
+                available: 3,
+            }
+        ));
+    }
+
+    #[test]
+    fn multi_gigabyte_length_is_recovered_keeping_declared_length() {
+        let bytes = chunk(TRACK_CHUNK_KIND, 0xFFFF_FFF0, &[1, 2, 3]);
+        let options = ParseOptions {
+            recover_chunk_lengths: true,
+            ..ParseOptions::default()
+        };
+
+        let chunks_file = ChunksFile::try_from_with_options(&bytes, &options).unwrap();
+
+        assert_eq!(chunks_file[0].length, 0xFFFF_FFF0);
+        assert_eq!(chunks_file[0].data, [1, 2, 3]);
+    }
+
+    #[test]
+    fn three_junk_bytes_after_last_chunk_are_trailing() {
+        let bytes = [HEADER, &[0xDE, 0xAD, 0xBE]].concat();
+
+        let chunks_file =
+            ChunksFile::try_from_with_options(&bytes, &ParseOptions::default()).unwrap();
+
+        assert_eq!(chunks_file.len(), 1);
+        assert_eq!(chunks_file.trailing, [0xDE, 0xAD, 0xBE]);
+    }
+
+    #[test]
+    fn unreadable_final_chunk_is_trailing_of_partial_read() {
+        let truncated = chunk(TRACK_CHUNK_KIND, 100, &[0x00, 0xFF, 0x2F, 0x00]);
+        let bytes = [HEADER, &truncated].concat();
+
+        let (chunks_file, error) = ChunksFile::try_from_partial(&bytes, &ParseOptions::default());
+
+        assert_eq!(chunks_file.len(), 1);
+        assert_eq!(chunks_file.trailing, truncated);
+        assert!(matches!(
+            error,
+            Some(TryFromError::LengthExceedsBuffer { .. })
+        ));
+    }
+
+    #[test]
+    fn unreadable_first_chunk_is_not_trailing() {
+        let (chunks_file, error) =
+            ChunksFile::try_from_partial(&[1, 2, 3], &ParseOptions::default());
+
+        assert!(chunks_file.is_empty());
+        assert!(chunks_file.trailing.is_empty());
+        assert!(matches!(error, Some(TryFromError::CouldNotReadKind)));
+    }
+
+    #[test]
+    #[cfg(feature = "file")]
+    fn typed_classifies_header_track_and_alien_chunks() {
+        let end_of_track = [0x00, 0xFF, 0x2F, 0x00];
+        let bytes = [
+            HEADER,
+            &chunk(b"XFIH", 2, b"ab"),
+            &chunk(TRACK_CHUNK_KIND, 4, &end_of_track),
+        ]
+        .concat();
+        let chunks_file =
+            ChunksFile::try_from_with_options(&bytes, &ParseOptions::default()).unwrap();
+
+        let typed_chunk_files = chunks_file.typed().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert!(matches!(
+            &typed_chunk_files[..],
+            [
+                TypedChunkFile::Header(header_chunk_file),
+                TypedChunkFile::Alien(alien_chunk_file),
+                TypedChunkFile::Track(track_chunk_file),
+            ] if header_chunk_file.division == &[0x00, 0x60]
+                && alien_chunk_file.kind == b"XFIH"
+                && alien_chunk_file.data == b"ab"
+                && track_chunk_file.track_events == end_of_track
+        ));
     }
 }
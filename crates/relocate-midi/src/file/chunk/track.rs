@@ -39,9 +39,11 @@ impl<'a> TryFrom<&ChunkFile<'a>> for TrackChunkFile<'a> {
             return Err(TryFromError::InvalidKind);
         }
 
+        // The data is shorter than the declared length if it was recovered,
+        // see `ParseOptions::recover_chunk_lengths`.
         let mut scanner = Scanner::new(value.data);
         let track_events = scanner
-            .eat_slice(value.length as usize)
+            .eat_slice(value.data.len().min(value.length as usize))
             .ok_or(TryFromError::CouldNotReadVLQ)?;
 
         if !scanner.done() {
@@ -55,3 +57,30 @@ impl<'a> TryFrom<&ChunkFile<'a>> for TrackChunkFile<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::chunk::header::HEADER_CHUNK_KIND;
+
+    fn chunk_file(kind: &'static [u8; 4]) -> ChunkFile<'static> {
+        ChunkFile {
+            kind,
+            length: 4,
+            offset: 0,
+            data: &[0x00, 0xFF, 0x2F, 0x00],
+        }
+    }
+
+    #[test]
+    fn only_track_chunk_kind_is_a_track() {
+        let track_chunk_file = TrackChunkFile::try_from(&chunk_file(TRACK_CHUNK_KIND)).unwrap();
+
+        assert_eq!(track_chunk_file.kind(), b"MTrk");
+        assert_eq!(track_chunk_file.track_events, &[0x00, 0xFF, 0x2F, 0x00]);
+        assert!(matches!(
+            TrackChunkFile::try_from(&chunk_file(HEADER_CHUNK_KIND)),
+            Err(TryFromError::InvalidKind)
+        ));
+    }
+}
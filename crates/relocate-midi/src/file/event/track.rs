@@ -1,7 +1,14 @@
+use alloc::vec::Vec;
+
 use derive_more::{Debug, Deref, Display, Error, IntoIterator};
-use log::warn;
+use log::{trace, warn};
 
-use crate::{file::chunk::track::TrackChunkFile, scanner::Scanner};
+pub use crate::core::event::channel::channel_message_data_len;
+use crate::{
+    file::chunk::track::TrackChunkFile,
+    options::{LenientMode, ParseOptions},
+    scanner::Scanner,
+};
 
 pub const TRACK_EVENT_DATA_00_MIN_MIDI_RUNNING: u8 = 0x00;
 pub const TRACK_EVENT_DATA_7F_MAX_MIDI_RUNNING: u8 = 0x7F;
@@ -66,13 +73,39 @@ pub enum TryFromError {
     CouldNotReadVLQ,
     CouldNotReadData,
     RunningStatusNotSet,
+    #[debug("InvalidStatus({:X})", _0)]
+    InvalidStatus(#[error(ignore)] u8),
+    #[display("MetaLengthExceedsTrack({declared}, {available})")]
+    MetaLengthExceedsTrack {
+        declared: u32,
+        available: usize,
+    },
+    #[display("SysExLengthExceedsTrack({declared}, {available})")]
+    SysExLengthExceedsTrack {
+        declared: u32,
+        available: usize,
+    },
 }
 
 impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
     type Error = TryFromError;
 
     fn try_from(value: &'a TrackChunkFile<'a>) -> Result<Self, Self::Error> {
-        let mut events = Vec::new();
+        TrackEventsFile::try_from_with_options(value, &ParseOptions::default())
+    }
+}
+
+impl<'a> TrackEventsFile<'a> {
+    /// Converts a [`TrackChunkFile`] like `TrackEventsFile::try_from`,
+    /// following `options`.
+    pub fn try_from_with_options(
+        value: &'a TrackChunkFile<'a>,
+        options: &ParseOptions,
+    ) -> Result<Self, TryFromError> {
+        // A heuristic rather than a count: the smallest event, a delta-time
+        // and a channel message with one data byte under running status,
+        // takes 2 bytes, but most events take at least 3.
+        let mut events = Vec::with_capacity(value.track_events.len() / 3);
         let mut scanner = Scanner::new(value.track_events);
         let mut running_status: Option<&'a u8> = None;
 
@@ -86,9 +119,9 @@ impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
             let event = match status_byte {
                 TRACK_EVENT_DATA_00_MIN_MIDI_RUNNING..=TRACK_EVENT_DATA_7F_MAX_MIDI_RUNNING => {
                     let status = running_status.ok_or(TryFromError::RunningStatusNotSet)?;
-                    running_status = Some(status);
+                    trace!("Reusing running status {:#X}.", status);
                     let data = scanner
-                        .eat_data_bytes()
+                        .eat_slice(channel_message_data_len(*status))
                         .ok_or(TryFromError::CouldNotReadData)?;
                     TrackEventFile {
                         delta_time,
@@ -100,7 +133,7 @@ impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
                     let status = scanner.eat().ok_or(TryFromError::CouldNotReadStatus)?;
                     running_status = Some(status);
                     let data = scanner
-                        .eat_data_bytes()
+                        .eat_slice(channel_message_data_len(*status))
                         .ok_or(TryFromError::CouldNotReadData)?;
                     TrackEventFile {
                         delta_time,
@@ -116,9 +149,12 @@ impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
                     let length = scanner
                         .eat_variable_length_quantity()
                         .ok_or(TryFromError::CouldNotReadVLQ)?;
-                    let data = scanner
-                        .eat_slice(length as usize)
-                        .ok_or(TryFromError::CouldNotReadData)?;
+                    let data = scanner.eat_slice(length as usize).ok_or(
+                        TryFromError::MetaLengthExceedsTrack {
+                            declared: length,
+                            available: scanner.rest().len(),
+                        },
+                    )?;
 
                     TrackEventFile {
                         delta_time,
@@ -138,9 +174,12 @@ impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
                     let length = scanner
                         .eat_variable_length_quantity()
                         .ok_or(TryFromError::CouldNotReadVLQ)?;
-                    let data = scanner
-                        .eat_slice(length as usize)
-                        .ok_or(TryFromError::CouldNotReadData)?;
+                    let data = scanner.eat_slice(length as usize).ok_or(
+                        TryFromError::SysExLengthExceedsTrack {
+                            declared: length,
+                            available: scanner.rest().len(),
+                        },
+                    )?;
 
                     TrackEventFile {
                         delta_time,
@@ -159,9 +198,12 @@ impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
                     let length = scanner
                         .eat_variable_length_quantity()
                         .ok_or(TryFromError::CouldNotReadVLQ)?;
-                    let data = scanner
-                        .eat_slice(length as usize)
-                        .ok_or(TryFromError::CouldNotReadData)?;
+                    let data = scanner.eat_slice(length as usize).ok_or(
+                        TryFromError::SysExLengthExceedsTrack {
+                            declared: length,
+                            available: scanner.rest().len(),
+                        },
+                    )?;
 
                     TrackEventFile {
                         delta_time,
@@ -179,10 +221,13 @@ impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
                 // appear, the file is non-conforming. In practice, many
                 // parsers choose to ignore these bytes or treat them as
                 // malformed data to maintain compatibility with legacy or
-                // poorly generated files.
+                // poorly generated files, so `options` decides.
                 TRACK_EVENT_STATUS_F1_MIN_SYS_COMMON..=TRACK_EVENT_STATUS_F6_MAX_SYS_COMMON
                 | TRACK_EVENT_STATUS_F8_MIN_SYS_REALTIME..=TRACK_EVENT_STATUS_FE_MAX_SYS_REALTIME =>
                 {
+                    if options.invalid_status == LenientMode::Error {
+                        return Err(TryFromError::InvalidStatus(status_byte));
+                    }
                     scanner.eat().ok_or(TryFromError::CouldNotReadStatus)?;
                     running_status = None;
                     warn!(
@@ -198,3 +243,120 @@ impl<'a> TryFrom<&'a TrackChunkFile<'a>> for TrackEventsFile<'a> {
         Ok(TrackEventsFile(events))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::file::chunk::{ChunkFile, track::TRACK_CHUNK_KIND};
+
+    /// Reads `bytes` as the events of a track chunk, following `options`, as
+    /// the delta-time, status and data of each event.
+    fn events(
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Vec<(u32, u8, Vec<u8>)>, TryFromError> {
+        let chunk_file = ChunkFile {
+            kind: TRACK_CHUNK_KIND,
+            length: bytes.len() as u32,
+            offset: 0,
+            data: bytes,
+        };
+        let track_chunk_file = TrackChunkFile::try_from(&chunk_file).unwrap();
+        let track_events_file = TrackEventsFile::try_from_with_options(&track_chunk_file, options)?;
+        Ok(track_events_file
+            .iter()
+            .map(|track_event_file| {
+                let (status, data) = match &track_event_file.event {
+                    EventFile::Meta(meta_event_file) => {
+                        (*meta_event_file.status, meta_event_file.data)
+                    }
+                    EventFile::SysEx(sys_ex_event_file) => {
+                        (*sys_ex_event_file.status, sys_ex_event_file.data)
+                    }
+                    EventFile::Midi(midi_event_file) => {
+                        (*midi_event_file.status, midi_event_file.data)
+                    }
+                };
+                (track_event_file.delta_time, status, data.to_vec())
+            })
+            .collect())
+    }
+
+    #[test]
+    fn truncated_track_is_an_error() {
+        let bytes = [
+            0x00, 0x90, 0x3C, 0x40, 0x60, 0xFF, 0x01, 0x02, b'h', b'i', 0x00, 0xF0, 0x01, 0xF7,
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let event_ends = [0, 4, 10, 14, 18];
+
+        for len in 0..=bytes.len() {
+            let result = events(&bytes[..len], &ParseOptions::default());
+            assert_eq!(result.is_ok(), event_ends.contains(&len), "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn meta_length_exceeding_track_is_reported() {
+        assert!(matches!(
+            events(&[0x00, 0xFF, 0x01, 0x05, 1, 2], &ParseOptions::default()),
+            Err(TryFromError::MetaLengthExceedsTrack {
+                declared: 5,
+                available: 2,
+            })
+        ));
+    }
+
+    #[test]
+    fn sys_ex_length_exceeding_track_is_reported() {
+        assert!(matches!(
+            events(&[0x00, 0xF0, 0x05, 1, 2], &ParseOptions::default()),
+            Err(TryFromError::SysExLengthExceedsTrack {
+                declared: 5,
+                available: 2,
+            })
+        ));
+    }
+
+    #[test]
+    fn running_status_note_on_stream() {
+        // A note-on, two more reusing its status, then a program change with
+        // a single data byte, also reused.
+        let bytes = [
+            0x00, 0x90, 0x3C, 0x40, 0x00, 0x40, 0x40, 0x00, 0x43, 0x40, 0x60, 0xC1, 0x05, 0x00,
+            0x06, 0x00, 0xFF, 0x2F, 0x00,
+        ];
+
+        assert_eq!(
+            events(&bytes, &ParseOptions::default()).unwrap(),
+            vec![
+                (0x00, 0x90, vec![0x3C, 0x40]),
+                (0x00, 0x90, vec![0x40, 0x40]),
+                (0x00, 0x90, vec![0x43, 0x40]),
+                (0x60, 0xC1, vec![0x05]),
+                (0x00, 0xC1, vec![0x06]),
+                (0x00, 0xFF, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_status_follows_policy() {
+        let bytes = [0x00, 0xF8, 0x00, 0x90, 0x3C, 0x40, 0x00, 0xFF, 0x2F, 0x00];
+        let error = ParseOptions {
+            invalid_status: LenientMode::Error,
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(
+            events(&bytes, &ParseOptions::default()).unwrap(),
+            vec![(0x00, 0x90, vec![0x3C, 0x40]), (0x00, 0xFF, vec![])]
+        );
+        assert!(matches!(
+            events(&bytes, &error),
+            Err(TryFromError::InvalidStatus(0xF8))
+        ));
+    }
+}
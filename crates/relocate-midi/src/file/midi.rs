@@ -1,4 +1,6 @@
-use derive_more::{Debug, Deref, Display};
+use alloc::{string::String, vec, vec::Vec};
+
+use derive_more::{Debug, Deref, Display, Error};
 
 /// To any file system, a [`MIDIFile`] is simply
 /// a [series of 8-bit bytes](Vec<u8>).
@@ -12,3 +14,81 @@ impl From<Vec<u8>> for MIDIFile {
         MIDIFile(bytes)
     }
 }
+
+#[derive(Debug, Display, Error)]
+pub enum HexError {
+    /// The input has an odd number of hex digits, so its last byte is
+    /// incomplete.
+    OddLength,
+
+    /// The input contains a character that is neither a hex digit nor
+    /// whitespace.
+    #[display("InvalidCharacter({_0:?})")]
+    InvalidCharacter(#[error(ignore)] char),
+}
+
+impl MIDIFile {
+    /// Creates a [`MIDIFile`] from a string of hex digits, each pair of them
+    /// being a byte, such as `"4d 54 68 64"` for `MThd`.
+    ///
+    /// Whitespace is ignored anywhere, so bytes may be grouped and split
+    /// across lines freely, which makes test inputs easy to write.
+    pub fn from_hex(s: &str) -> Result<MIDIFile, HexError> {
+        let digits: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(HexError::InvalidCharacter(c));
+        }
+
+        let mut bytes = vec![0; digits.len() / 2];
+        hex::decode_to_slice(&digits, &mut bytes).map_err(|_| HexError::OddLength)?;
+        Ok(MIDIFile(bytes))
+    }
+}
+
+#[cfg(feature = "std")]
+impl MIDIFile {
+    /// Reads the whole file at `path` into a [`MIDIFile`].
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<MIDIFile> {
+        std::fs::read(path).map(MIDIFile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn from_path_reads_bundled_asset() {
+        let midi_file = MIDIFile::from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../assets/Lapis Lazuli.mid"
+        ))
+        .unwrap();
+
+        assert_eq!(&midi_file[..4], b"MThd");
+    }
+
+    #[test]
+    fn from_hex_ignores_whitespace() {
+        assert_eq!(&*MIDIFile::from_hex("4d 54 68 64").unwrap(), b"MThd");
+        assert_eq!(&*MIDIFile::from_hex("4D5468\n\t64  ").unwrap(), b"MThd");
+        assert!(MIDIFile::from_hex("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn from_hex_rejects_odd_length_and_non_hex_input() {
+        assert!(matches!(
+            MIDIFile::from_hex("4d 5"),
+            Err(HexError::OddLength)
+        ));
+        assert!(matches!(
+            MIDIFile::from_hex("4d 5g"),
+            Err(HexError::InvalidCharacter('g'))
+        ));
+        assert!(matches!(
+            MIDIFile::from_hex("4d é5"),
+            Err(HexError::InvalidCharacter('é'))
+        ));
+    }
+}
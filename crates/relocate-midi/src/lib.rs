@@ -1,6 +1,41 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod container;
 pub mod core;
+#[cfg(feature = "std")]
+pub mod error;
 #[cfg(feature = "file")]
 pub mod file;
 #[cfg(not(feature = "file"))]
 mod file;
-mod scanner;
+#[cfg(feature = "live")]
+pub mod live;
+pub mod options;
+pub mod prelude;
+pub mod scanner;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod strict;
+mod writer;
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::core::midi::MIDI;
+
+    /// Parses and writes back a file through the API available without
+    /// `std`, run by `cargo test -p relocate-midi --no-default-features`.
+    /// The tests are checked to compile in that configuration by
+    /// `cargo clippy -p relocate-midi --no-default-features --all-targets -- -D warnings`.
+    #[test]
+    fn round_trip_without_std() {
+        let bytes = b"MThd\0\0\0\x06\0\0\0\x01\0\x60MTrk\0\0\0\x04\0\xFF\x2F\0".to_vec();
+
+        let midi = MIDI::try_from(bytes.clone()).unwrap();
+
+        assert_eq!(Vec::try_from(&midi).unwrap(), bytes);
+    }
+}
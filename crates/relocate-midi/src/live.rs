@@ -0,0 +1,246 @@
+//! A module decoding a live MIDI byte stream, as received from a MIDI input,
+//! into [`MIDIMessage`]s.
+//!
+//! Unlike a track chunk, a live stream has no delta-times, and System
+//! Real-Time bytes may appear anywhere, even in the middle of another message.
+
+use alloc::vec::Vec;
+
+use arrayvec::ArrayVec;
+use derive_more::Debug;
+
+use crate::{
+    core::event::channel::{ChannelEvent, channel_message_data_len},
+    file::event::track::{
+        TRACK_EVENT_STATUS_80_MIN_MIDI, TRACK_EVENT_STATUS_EF_MAX_MIDI, TRACK_EVENT_STATUS_F0_SOX,
+        TRACK_EVENT_STATUS_F1_MIN_SYS_COMMON, TRACK_EVENT_STATUS_F6_MAX_SYS_COMMON,
+        TRACK_EVENT_STATUS_F7_EOX, TRACK_EVENT_STATUS_F8_MIN_SYS_REALTIME,
+    },
+};
+
+/// A complete message received from a live MIDI stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MIDIMessage {
+    /// A channel voice message.
+    Channel(ChannelEvent),
+
+    /// A System Exclusive message, without its leading `F0` and trailing `F7`.
+    SysEx(Vec<u8>),
+
+    /// A System Common message (`F1`-`F6`) with its data bytes.
+    #[debug("SystemCommon({:X}, {:?})", status, data)]
+    SystemCommon { status: u8, data: Vec<u8> },
+
+    /// A System Real-Time message (`F8`-`FF`), which has no data bytes.
+    #[debug("RealTime({:X})", _0)]
+    RealTime(u8),
+}
+
+/// Decodes a live MIDI byte stream into [`MIDIMessage`]s.
+///
+/// Bytes may be pushed one at a time or in chunks of any size; a message is
+/// emitted as soon as its last byte is received. Running status is honored
+/// for channel voice messages, and System Real-Time bytes are emitted
+/// immediately without disturbing the message they interrupt.
+#[derive(Debug, Default)]
+pub struct LiveDecoder {
+    /// The status byte of the message being received, if any.
+    status: Option<u8>,
+
+    /// The data bytes received so far for the current message.
+    data: Vec<u8>,
+}
+
+impl LiveDecoder {
+    /// Creates a new `LiveDecoder` waiting for a status byte.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a single `byte` into the decoder, returning the message it
+    /// completes, if any.
+    pub fn push_byte(&mut self, byte: u8) -> Option<MIDIMessage> {
+        match byte {
+            TRACK_EVENT_STATUS_F8_MIN_SYS_REALTIME..=u8::MAX => Some(MIDIMessage::RealTime(byte)),
+
+            TRACK_EVENT_STATUS_F7_EOX => match self.status.take() {
+                Some(TRACK_EVENT_STATUS_F0_SOX) => {
+                    Some(MIDIMessage::SysEx(::core::mem::take(&mut self.data)))
+                }
+                _ => {
+                    // A stray End of Exclusive carries nothing; it only
+                    // cancels running status like any System Common status.
+                    self.data.clear();
+                    None
+                }
+            },
+
+            TRACK_EVENT_STATUS_80_MIN_MIDI..=TRACK_EVENT_STATUS_EF_MAX_MIDI
+            | TRACK_EVENT_STATUS_F0_SOX
+            | TRACK_EVENT_STATUS_F1_MIN_SYS_COMMON..=TRACK_EVENT_STATUS_F6_MAX_SYS_COMMON => {
+                // Any status byte other than System Real-Time terminates the
+                // previous message, including an unfinished System Exclusive.
+                self.status = Some(byte);
+                self.data.clear();
+                self.complete()
+            }
+
+            _ => {
+                // A data byte is only meaningful once a status is known.
+                self.status?;
+                self.data.push(byte);
+                self.complete()
+            }
+        }
+    }
+
+    /// Feeds a chunk of `bytes` into the decoder, returning every message
+    /// completed by them in order.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<MIDIMessage> {
+        bytes
+            .iter()
+            .filter_map(|byte| self.push_byte(*byte))
+            .collect()
+    }
+
+    /// Emits the current message if all of its data bytes have arrived.
+    fn complete(&mut self) -> Option<MIDIMessage> {
+        let status = self.status?;
+        match status {
+            TRACK_EVENT_STATUS_80_MIN_MIDI..=TRACK_EVENT_STATUS_EF_MAX_MIDI => {
+                if self.data.len() != channel_message_data_len(status) {
+                    return None;
+                }
+                // Keep the status for running status.
+                let data = ::core::mem::take(&mut self.data);
+                ChannelEvent::from_parts(status, &data)
+                    .ok()
+                    .map(MIDIMessage::Channel)
+            }
+
+            TRACK_EVENT_STATUS_F1_MIN_SYS_COMMON..=TRACK_EVENT_STATUS_F6_MAX_SYS_COMMON => {
+                let length = match status {
+                    0xF1 | 0xF3 => 1,
+                    0xF2 => 2,
+                    _ => 0,
+                };
+                if self.data.len() != length {
+                    return None;
+                }
+                // System Common messages cancel running status.
+                self.status = None;
+                Some(MIDIMessage::SystemCommon {
+                    status,
+                    data: ::core::mem::take(&mut self.data),
+                })
+            }
+
+            _ => None,
+        }
+    }
+}
+
+impl ChannelEvent {
+    /// Encodes this [`ChannelEvent`] as a live MIDI message: its status byte
+    /// followed by its 1 or 2 data bytes, without delta-time or running
+    /// status. The least significant byte of a pitch bend comes first.
+    ///
+    /// The result can be sent as is to a MIDI output, such as `midir`'s
+    /// `MidiOutputConnection::send`.
+    pub fn to_wire_bytes(&self) -> ArrayVec<u8, 3> {
+        let (bytes, len) = self.encode();
+        bytes[..len].iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::core::event::channel::{Channel, ChannelMessage};
+
+    fn note_on(channel: u8, key: u8, velocity: u8) -> MIDIMessage {
+        MIDIMessage::Channel(ChannelEvent {
+            channel: Channel::new(channel).unwrap(),
+            message: ChannelMessage::NoteOn { key, velocity },
+        })
+    }
+
+    #[test]
+    fn push_joins_message_split_across_calls() {
+        let mut decoder = LiveDecoder::new();
+
+        assert!(decoder.push(&[0x90, 0x3C]).is_empty());
+        assert_eq!(decoder.push(&[0x40]), [note_on(0, 0x3C, 0x40)]);
+    }
+
+    #[test]
+    fn push_passes_real_time_through_messages() {
+        let mut decoder = LiveDecoder::new();
+
+        assert_eq!(
+            decoder.push(&[
+                0x90, 0x3C, 0xF8, 0x40, 0x3E, 0x40, 0xF0, 1, 2, 0xFE, 0xF7, 0xF2, 1, 2
+            ]),
+            [
+                MIDIMessage::RealTime(0xF8),
+                note_on(0, 0x3C, 0x40),
+                note_on(0, 0x3E, 0x40),
+                MIDIMessage::RealTime(0xFE),
+                MIDIMessage::SysEx(vec![1, 2]),
+                MIDIMessage::SystemCommon {
+                    status: 0xF2,
+                    data: vec![1, 2],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn to_wire_bytes_of_known_messages() {
+        use crate::core::event::controller::Controller;
+
+        let wire_bytes = |channel, message| {
+            ChannelEvent {
+                channel: Channel::new(channel).unwrap(),
+                message,
+            }
+            .to_wire_bytes()
+        };
+
+        assert_eq!(
+            *wire_bytes(
+                0,
+                ChannelMessage::NoteOn {
+                    key: 60,
+                    velocity: 100
+                }
+            ),
+            [0x90, 60, 100]
+        );
+        assert_eq!(
+            *wire_bytes(15, ChannelMessage::ProgramChange { program: 5 }),
+            [0xCF, 5]
+        );
+        assert_eq!(
+            *wire_bytes(1, ChannelMessage::PitchBend { value: 0x2001 }),
+            [0xE1, 0x01, 0x40]
+        );
+        assert_eq!(
+            *wire_bytes(
+                2,
+                ChannelMessage::ControlChange {
+                    controller: Controller::PAN,
+                    value: 64
+                }
+            ),
+            [0xB2, 0x0A, 64]
+        );
+        assert_eq!(
+            *wire_bytes(3, ChannelMessage::ChannelPressure { pressure: 9 }),
+            [0xD3, 9]
+        );
+    }
+}
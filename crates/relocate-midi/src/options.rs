@@ -0,0 +1,82 @@
+//! A module providing options to tune how lenient parsing is, and which
+//! events analysis considers.
+
+use alloc::string::String;
+
+use crate::core::event::channel::{Channel, ChannelSet};
+
+/// Options controlling how a [`MIDI`](crate::core::midi::MIDI) is parsed.
+///
+/// The [`Default`] options are the ones used by the `TryFrom` conversions.
+/// They are strict, except that stray status bytes in track chunks are
+/// skipped, see [`LenientMode`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// Whether to recover from a chunk whose declared length runs past the
+    /// end of the file, by ending it at the next `MThd` or `MTrk` magic
+    /// instead of failing with `LengthExceedsBuffer`.
+    pub recover_chunk_lengths: bool,
+
+    /// Whether to append an `EndOfTrack` meta-event, with a delta-time of
+    /// zero, to a track chunk that does not end with one.
+    pub synthesize_end_of_track: bool,
+
+    /// What to do with a System Common (`F1`-`F6`) or System Real-Time
+    /// (`F8`-`FE`) status byte in a track chunk.
+    pub invalid_status: LenientMode,
+
+    /// The encoding of the text of meta-events, such as
+    /// [`encoding_rs::SHIFT_JIS`] for many Japanese karaoke files, or `None`
+    /// for UTF-8.
+    #[cfg(feature = "encoding")]
+    pub text_encoding: Option<&'static encoding_rs::Encoding>,
+}
+
+impl ParseOptions {
+    /// Decodes the text of a meta-event with `text_encoding`, or as UTF-8,
+    /// replacing invalid sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    pub(crate) fn decode_text(&self, data: &[u8]) -> String {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.text_encoding {
+            return encoding.decode_without_bom_handling(data).0.into_owned();
+        }
+        String::from_utf8_lossy(data).into_owned()
+    }
+}
+
+/// How to treat a status byte that is not valid in a track chunk.
+///
+/// According to the SMF specification, System Common and System Real-Time
+/// messages are not valid events within a MIDI file, yet some legacy or
+/// poorly generated files contain them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LenientMode {
+    /// Skip the status byte, logging a warning, and keep reading the track.
+    #[default]
+    SkipWithWarning,
+
+    /// Fail with `InvalidStatus`.
+    Error,
+}
+
+/// Options controlling which events the analysis and editing methods of
+/// [`MIDI`](crate::core::midi::MIDI) and
+/// [`TrackChunk`](crate::core::chunk::track::TrackChunk) consider.
+///
+/// The [`Default`] options consider every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnalysisOptions {
+    /// The channels whose events are left out, such as
+    /// [`DRUM_CHANNEL`](crate::core::event::channel::DRUM_CHANNEL) for pitch
+    /// analysis or transposition, since its keys select drum sounds rather
+    /// than pitches.
+    pub exclude_channels: ChannelSet,
+}
+
+impl AnalysisOptions {
+    /// Whether an event on `channel` is considered.
+    #[inline]
+    pub fn includes(&self, channel: Channel) -> bool {
+        !self.exclude_channels.contains(channel)
+    }
+}
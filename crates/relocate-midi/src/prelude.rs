@@ -0,0 +1,27 @@
+//! A module re-exporting the commonly used types of this crate, so that
+//! they can all be imported at once:
+//!
+//! ```
+//! use relocate_midi::prelude::*;
+//! ```
+
+pub use crate::{
+    core::{
+        chunk::{
+            Chunk,
+            header::{
+                HeaderChunk,
+                division::{Division, fps::Fps},
+                format::Format,
+            },
+            track::{TrackChunk, TrackEvent},
+        },
+        event::{
+            Event,
+            channel::{Channel, ChannelEvent, ChannelMessage},
+            meta::MetaEvent,
+        },
+        midi::MIDI,
+    },
+    scanner::Scanner,
+};
@@ -27,6 +27,19 @@ impl<'a> Scanner<'a> {
         self.cursor == self.bytes.len()
     }
 
+    /// The index of the next byte to consume, that is, how many bytes have
+    /// been consumed so far.
+    #[inline]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The subslice after the cursor, without consuming it.
+    #[inline]
+    pub fn rest(&self) -> &'a [u8] {
+        self.after()
+    }
+
     /// The subslice after the cursor.
     #[inline]
     fn after(&self) -> &'a [u8] {
@@ -70,10 +83,37 @@ impl<'a> Scanner<'a> {
     }
 
     /// Consume and return exactly N bytes as a borrowed array.
+    ///
+    /// See [`Scanner::eat_array`] for an owned copy.
     #[inline]
     pub fn eat_bytes<const N: usize>(&mut self) -> Option<&'a [u8; N]> {
         self.eat_slice(N)?.try_into().ok()
     }
+
+    /// Consume and return exactly N bytes as an owned array, copied out of
+    /// the byte slice.
+    ///
+    /// See [`Scanner::eat_bytes`] for a reference into the byte slice.
+    #[inline]
+    pub fn eat_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.eat_bytes().copied()
+    }
+
+    /// Consume and return the bytes before the next `delimiter`, such as the
+    /// `0x00` ending a null-terminated string.
+    ///
+    /// The `delimiter` itself is not part of the result, but is consumed too
+    /// if `eat_delimiter` is `true`. If there is no `delimiter` left, returns
+    /// `None` and does not advance the cursor.
+    #[inline]
+    pub fn eat_until_byte(&mut self, delimiter: u8, eat_delimiter: bool) -> Option<&'a [u8]> {
+        let n = self.after().iter().position(|byte| *byte == delimiter)?;
+        let result = self.eat_slice(n)?;
+        if eat_delimiter {
+            self.eat();
+        }
+        Some(result)
+    }
 }
 
 impl<'a> Scanner<'a> {
@@ -84,6 +124,20 @@ impl<'a> Scanner<'a> {
         Some(u16::from_be_bytes(*bytes))
     }
 
+    /// Consume and return a byte as an i8 in two's complement form.
+    #[inline]
+    pub fn eat_i8(&mut self) -> Option<i8> {
+        let bytes = self.eat_bytes::<1>()?;
+        Some(i8::from_be_bytes(*bytes))
+    }
+
+    /// Consume and return an i16 in big-endian, two's complement format.
+    #[inline]
+    pub fn eat_i16_be(&mut self) -> Option<i16> {
+        let bytes = self.eat_bytes::<2>()?;
+        Some(i16::from_be_bytes(*bytes))
+    }
+
     /// Consume and return a u32 in big-endian format.
     #[inline]
     pub fn eat_u32_be(&mut self) -> Option<u32> {
@@ -97,10 +151,26 @@ impl<'a> Scanner<'a> {
     /// If the variable-length quantity is malformed (e.g., incomplete or
     /// exceeds the maximum size), returns `None`.
     pub fn eat_variable_length_quantity(&mut self) -> Option<u32> {
-        let mut value: u32 = 0;
-        for _ in 0..4 {
+        // 4 bytes hold at most 28 bits, the largest value the MIDI
+        // Specification allows.
+        let value = self.eat_variable_length_quantity_max(4)?;
+        u32::try_from(value).ok()
+    }
+
+    /// Consume and return a variable-length quantity value like
+    /// [`Scanner::eat_variable_length_quantity`], but spanning up to
+    /// `max_bytes` bytes instead of 4, as some proprietary extensions do.
+    ///
+    /// If the variable-length quantity is malformed (e.g., incomplete,
+    /// longer than `max_bytes` or too large for a u64), returns `None`.
+    pub fn eat_variable_length_quantity_max(&mut self, max_bytes: usize) -> Option<u64> {
+        let mut value: u64 = 0;
+        for _ in 0..max_bytes {
             let byte = self.eat()?;
-            value = (value << 7) | u32::from(byte & 0x7F);
+            if value >> (u64::BITS - 7) != 0 {
+                return None;
+            }
+            value = (value << 7) | u64::from(byte & 0x7F);
             if byte & 0x80 == 0 {
                 return Some(value);
             }
@@ -108,26 +178,121 @@ impl<'a> Scanner<'a> {
         None
     }
 
-    /// Consume bytes until a byte with the high bit set is found, returning
-    /// the consumed bytes as a slice (not including the high-bit byte).
-    ///
-    /// This is used to read MIDI data bytes (which have high bit = 0) until
-    /// we encounter a status byte (high bit = 1), which we do NOT consume.
+    /// Consume and return a variable-length quantity value like
+    /// [`Scanner::eat_variable_length_quantity`], but reject one encoded with
+    /// redundant leading bytes.
     ///
-    /// Returns the slice of consumed data bytes, or None if we reach the end
-    /// without finding a high-bit byte.
-    pub fn eat_data_bytes(&mut self) -> Option<&'a [u8]> {
-        let start_cursor = self.cursor;
-        while let Some(byte) = self.peek() {
-            if byte & 0x80 != 0 {
-                // Found a byte with high bit set, stop without consuming it
-                let end_cursor = self.cursor;
-                return Some(&self.bytes[start_cursor..end_cursor]);
-            }
-            // Consume the byte with high bit = 0
-            self.eat();
+    /// A leading `0x80` continues the quantity while contributing nothing to
+    /// its value, so the same value always has a shorter encoding. In that
+    /// case, returns `None` without advancing the cursor.
+    pub fn eat_variable_length_quantity_strict(&mut self) -> Option<u32> {
+        if self.peek() == Some(&0x80) {
+            return None;
+        }
+        self.eat_variable_length_quantity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eat_variable_length_quantity() {
+        for (bytes, expected, expected_strict) in [
+            (&[0x00][..], Some(0x00), Some(0x00)),
+            (&[0x40], Some(0x40), Some(0x40)),
+            (&[0x7F], Some(0x7F), Some(0x7F)),
+            (&[0x81, 0x00], Some(0x80), Some(0x80)),
+            (&[0xC0, 0x00], Some(0x2000), Some(0x2000)),
+            (&[0xFF, 0x7F], Some(0x3FFF), Some(0x3FFF)),
+            (&[0x81, 0x80, 0x00], Some(0x4000), Some(0x4000)),
+            (&[0xFF, 0xFF, 0x7F], Some(0x1F_FFFF), Some(0x1F_FFFF)),
+            (&[0x81, 0x80, 0x80, 0x00], Some(0x20_0000), Some(0x20_0000)),
+            (
+                &[0xFF, 0xFF, 0xFF, 0x7F],
+                Some(0x0FFF_FFFF),
+                Some(0x0FFF_FFFF),
+            ),
+            // A leading `0x80` is a redundant continuation with value 0.
+            (&[0x80, 0x0F], Some(0x0F), None),
+            (&[0x80, 0x80, 0x00], Some(0x00), None),
+            // Unterminated, or longer than 4 bytes.
+            (&[0x81], None, None),
+            (&[0x81, 0x80, 0x80, 0x80, 0x00], None, None),
+        ] {
+            assert_eq!(
+                Scanner::new(bytes).eat_variable_length_quantity(),
+                expected,
+                "{bytes:X?}"
+            );
+            assert_eq!(
+                Scanner::new(bytes).eat_variable_length_quantity_strict(),
+                expected_strict,
+                "{bytes:X?}"
+            );
         }
-        // Reached the end of the slice, return the consumed bytes
-        Some(&self.bytes[start_cursor..self.cursor])
+    }
+
+    #[test]
+    fn eat_signed_integers() {
+        let mut scanner = Scanner::new(&[0xFD, 0xFF, 0xFE, 0x01]);
+
+        assert_eq!(scanner.eat_i8(), Some(-3));
+        assert_eq!(scanner.eat_i16_be(), Some(-2));
+        assert_eq!(scanner.eat_i16_be(), None);
+    }
+
+    #[test]
+    fn eat_variable_length_quantity_max_accepts_longer_quantities() {
+        let five_bytes = [0x81, 0x80, 0x80, 0x80, 0x00];
+        assert_eq!(
+            Scanner::new(&five_bytes).eat_variable_length_quantity(),
+            None
+        );
+        assert_eq!(
+            Scanner::new(&five_bytes).eat_variable_length_quantity_max(5),
+            Some(1 << 28)
+        );
+        assert_eq!(
+            Scanner::new(&five_bytes).eat_variable_length_quantity_max(4),
+            None
+        );
+
+        let max = [0x81, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F];
+        assert_eq!(
+            Scanner::new(&max).eat_variable_length_quantity_max(10),
+            Some(u64::MAX)
+        );
+        let overflow = [0x82, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F];
+        assert_eq!(
+            Scanner::new(&overflow).eat_variable_length_quantity_max(10),
+            None
+        );
+    }
+
+    #[test]
+    fn eat_until_byte_with_null_terminated_strings() {
+        let mut scanner = Scanner::new(b"Roland\0SC-88\0rest");
+
+        assert_eq!(scanner.eat_until_byte(0, true), Some(&b"Roland"[..]));
+        assert_eq!(scanner.eat_until_byte(0, false), Some(&b"SC-88"[..]));
+        assert_eq!(scanner.eat(), Some(&0));
+        assert_eq!(scanner.eat_until_byte(0, true), None);
+        assert_eq!(scanner.rest(), b"rest");
+    }
+
+    #[test]
+    fn eat_bytes_borrows_and_eat_array_copies() {
+        let bytes = [1, 2, 3, 4, 5];
+        let mut scanner = Scanner::new(&bytes);
+
+        assert_eq!(scanner.eat_bytes::<2>(), Some(&[1, 2]));
+        assert_eq!(scanner.eat_array::<2>(), Some([3, 4]));
+        assert_eq!(scanner.eat_array::<2>(), None);
+        assert_eq!(scanner.eat_bytes::<2>(), None);
+        assert_eq!(scanner.cursor(), 4);
+        assert_eq!(scanner.eat_array::<1>(), Some([5]));
+        assert!(scanner.done());
     }
 }
@@ -0,0 +1,314 @@
+//! A module reading the events of a track chunk from an [`io::Read`], such as
+//! a file or a socket, without loading the whole track chunk in memory.
+//!
+//! There is no buffer to borrow from, so events are read into [`TrackEvent`]s,
+//! which own their data. They are decoded exactly as `TrackChunk::try_from`
+//! does, so both paths yield equal events for the same track chunk.
+
+use alloc::{vec, vec::Vec};
+use std::io::{self, Read};
+
+use derive_more::{Debug, Display, Error};
+use log::{debug, trace, warn};
+
+use crate::{
+    core::{
+        chunk::track::TrackEvent,
+        event::{Event, channel::channel_message_data_len, meta::MetaEvent, sysex::SplitSysEx},
+        ticks::DeltaTicks,
+    },
+    file::event::track::{
+        EventFile, MIDIEventFile, MetaEventFile, SysExEventFile,
+        TRACK_EVENT_DATA_00_MIN_MIDI_RUNNING, TRACK_EVENT_DATA_7F_MAX_MIDI_RUNNING,
+        TRACK_EVENT_STATUS_80_MIN_MIDI, TRACK_EVENT_STATUS_EF_MAX_MIDI, TRACK_EVENT_STATUS_F0_SOX,
+        TRACK_EVENT_STATUS_F7_EOX, TRACK_EVENT_STATUS_FF_META,
+    },
+    options::{LenientMode, ParseOptions},
+};
+
+#[derive(Debug, Display, Error)]
+pub enum ReadError {
+    /// The reader failed, or ended in the middle of an event.
+    Io(io::Error),
+
+    CouldNotReadVLQ,
+
+    RunningStatusNotSet,
+
+    #[debug("InvalidStatus({:X})", _0)]
+    InvalidStatus(#[error(ignore)] u8),
+
+    EventFileToEvent(crate::core::event::TryFromError),
+}
+
+/// Reads the [`TrackEvent`]s of a track chunk from a reader, one at a time.
+///
+/// The reader must start at the first event of the track chunk, right after
+/// its kind and length, and end with the track chunk, such as a
+/// [`Read::take`] of its length. Reading stops at the end of the reader, or
+/// after the first error.
+#[derive(Debug)]
+pub struct TrackEventReader<R> {
+    #[debug(skip)]
+    reader: R,
+
+    options: ParseOptions,
+
+    /// The status byte of the last channel voice event, if no meta or system
+    /// exclusive event came since.
+    running_status: Option<u8>,
+
+    split_sys_ex: SplitSysEx,
+
+    /// Whether the last event read is [`MetaEvent::EndOfTrack`].
+    ends_with_end_of_track: bool,
+
+    done: bool,
+}
+
+impl<R: Read> TrackEventReader<R> {
+    /// Creates a new `TrackEventReader` reading from `reader` like
+    /// `TrackChunk::try_from`.
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        TrackEventReader::with_options(reader, &ParseOptions::default())
+    }
+
+    /// Creates a new `TrackEventReader` reading from `reader` like
+    /// `TrackChunk::try_from_with_options`, following `options`.
+    pub fn with_options(reader: R, options: &ParseOptions) -> Self {
+        TrackEventReader {
+            reader,
+            options: *options,
+            running_status: None,
+            split_sys_ex: SplitSysEx::default(),
+            ends_with_end_of_track: false,
+            done: false,
+        }
+    }
+
+    /// Reads the next event, or returns `None` at the end of the reader.
+    fn read_event(&mut self) -> Result<Option<TrackEvent>, ReadError> {
+        loop {
+            let Some(first_byte) = self.read_first_byte()? else {
+                return Ok(None);
+            };
+            let delta_time = DeltaTicks(self.read_variable_length_quantity(first_byte)?);
+            let status_byte = self.read_byte()?;
+
+            let mut kind = match status_byte {
+                TRACK_EVENT_DATA_00_MIN_MIDI_RUNNING..=TRACK_EVENT_DATA_7F_MAX_MIDI_RUNNING => {
+                    let status = self.running_status.ok_or(ReadError::RunningStatusNotSet)?;
+                    trace!("Reusing running status {:#X}.", status);
+                    // The status byte was the first data byte under running
+                    // status.
+                    let mut data = vec![0; channel_message_data_len(status)];
+                    data[0] = status_byte;
+                    self.read_exact(&mut data[1..])?;
+                    self.event_from(&EventFile::Midi(MIDIEventFile {
+                        status: &status,
+                        data: &data,
+                    }))?
+                }
+
+                TRACK_EVENT_STATUS_80_MIN_MIDI..=TRACK_EVENT_STATUS_EF_MAX_MIDI => {
+                    self.running_status = Some(status_byte);
+                    let mut data = vec![0; channel_message_data_len(status_byte)];
+                    self.read_exact(&mut data)?;
+                    self.event_from(&EventFile::Midi(MIDIEventFile {
+                        status: &status_byte,
+                        data: &data,
+                    }))?
+                }
+
+                TRACK_EVENT_STATUS_FF_META => {
+                    self.running_status = None;
+                    let kind = self.read_byte()?;
+                    let first_byte = self.read_byte()?;
+                    let length = self.read_variable_length_quantity(first_byte)?;
+                    let data = self.read_data(length)?;
+                    self.event_from(&EventFile::Meta(MetaEventFile {
+                        status: &TRACK_EVENT_STATUS_FF_META,
+                        kind: &kind,
+                        length,
+                        data: &data,
+                    }))?
+                }
+
+                TRACK_EVENT_STATUS_F0_SOX | TRACK_EVENT_STATUS_F7_EOX => {
+                    self.running_status = None;
+                    let first_byte = self.read_byte()?;
+                    let length = self.read_variable_length_quantity(first_byte)?;
+                    let data = self.read_data(length)?;
+                    let status = match status_byte {
+                        TRACK_EVENT_STATUS_F0_SOX => &TRACK_EVENT_STATUS_F0_SOX,
+                        _ => &TRACK_EVENT_STATUS_F7_EOX,
+                    };
+                    self.event_from(&EventFile::SysEx(SysExEventFile {
+                        status,
+                        length,
+                        data: &data,
+                    }))?
+                }
+
+                // System Common and System Real-Time status bytes, which are
+                // not valid in a track chunk, see `TrackEventsFile`.
+                _ => {
+                    if self.options.invalid_status == LenientMode::Error {
+                        return Err(ReadError::InvalidStatus(status_byte));
+                    }
+                    self.running_status = None;
+                    warn!(
+                        "Encountered invalid status byte {:#X} in MIDI file. Skipping event.",
+                        status_byte
+                    );
+                    continue;
+                }
+            };
+
+            if let Event::SysEx(sys_ex_event) = &mut kind {
+                self.split_sys_ex.classify(sys_ex_event);
+            }
+            return Ok(Some(TrackEvent { delta_time, kind }));
+        }
+    }
+
+    fn event_from(&self, event_file: &EventFile) -> Result<Event, ReadError> {
+        Event::try_from_with_options(event_file, &self.options).map_err(ReadError::EventFileToEvent)
+    }
+
+    /// Reads the first byte of an event, or returns `None` at the end of the
+    /// reader.
+    fn read_first_byte(&mut self) -> Result<Option<u8>, ReadError> {
+        let mut byte = [0];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(ReadError::Io(error)),
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<u8, ReadError> {
+        let mut byte = [0];
+        self.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), ReadError> {
+        self.reader.read_exact(buffer).map_err(ReadError::Io)
+    }
+
+    /// Reads `length` bytes, growing the buffer as they arrive rather than
+    /// trusting `length` up front.
+    fn read_data(&mut self, length: u32) -> Result<Vec<u8>, ReadError> {
+        let mut data = Vec::new();
+        (&mut self.reader)
+            .take(u64::from(length))
+            .read_to_end(&mut data)
+            .map_err(ReadError::Io)?;
+        if data.len() != length as usize {
+            return Err(ReadError::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(data)
+    }
+
+    /// Reads the rest of a variable-length quantity starting with
+    /// `first_byte`, like [`Scanner::eat_variable_length_quantity`].
+    ///
+    /// [`Scanner::eat_variable_length_quantity`]: crate::scanner::Scanner::eat_variable_length_quantity
+    fn read_variable_length_quantity(&mut self, first_byte: u8) -> Result<u32, ReadError> {
+        let mut byte = first_byte;
+        let mut value: u32 = 0;
+        for index in 0..4 {
+            if index != 0 {
+                byte = self.read_byte()?;
+            }
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ReadError::CouldNotReadVLQ)
+    }
+}
+
+impl<R: Read> Iterator for TrackEventReader<R> {
+    type Item = Result<TrackEvent, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_event() {
+            Ok(Some(track_event)) => {
+                self.ends_with_end_of_track =
+                    matches!(track_event.kind, Event::Meta(MetaEvent::EndOfTrack));
+                Some(Ok(track_event))
+            }
+            Ok(None) => {
+                self.done = true;
+                if !self.options.synthesize_end_of_track || self.ends_with_end_of_track {
+                    return None;
+                }
+                debug!("Track chunk does not end with EndOfTrack. Synthesizing one.");
+                Some(Ok(TrackEvent {
+                    delta_time: DeltaTicks(0),
+                    kind: Event::Meta(MetaEvent::EndOfTrack),
+                }))
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        chunk::{ChunkKind, chunk_infos},
+        midi::MIDI,
+    };
+
+    /// A reader yielding a single byte per read, the worst case for the
+    /// buffering of [`TrackEventReader`].
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let (Some(byte), Some((first, rest))) = (buf.first_mut(), self.0.split_first()) else {
+                return Ok(0);
+            };
+            *byte = *first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn streamed_events_equal_in_memory_events() {
+        let bytes = include_bytes!("../../../assets/Lapis Lazuli.mid");
+        let midi = MIDI::try_from(bytes.to_vec()).unwrap();
+        let track_infos = chunk_infos(bytes)
+            .unwrap()
+            .into_iter()
+            .filter(|chunk_info| chunk_info.kind == ChunkKind::Track);
+
+        for (chunk_info, track_chunk) in track_infos.zip(midi.tracks()) {
+            let start = chunk_info.offset + 8;
+            let data = &bytes[start..start + chunk_info.length as usize];
+
+            let streamed = TrackEventReader::new(OneByteReader(data))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(streamed, track_chunk.0);
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! A module parsing a [`MIDI`] while rejecting anything that does not conform
+//! to the Standard MIDI File specification.
+//!
+//! The `TryFrom` conversions are lenient where real-world files commonly
+//! deviate, see [`ParseOptions`]. [`parse_strict`] is the single entry point
+//! for validators, reporting the offset of the first deviation.
+
+use alloc::vec::Vec;
+
+use derive_more::{Debug, Display, Error};
+
+use crate::{
+    core::{chunk::Chunk, event::channel::channel_message_data_len, midi::MIDI},
+    file::{
+        chunk::{
+            ChunkFile,
+            header::{HEADER_CHUNK_KIND, HEADER_CHUNK_LENGTH},
+            track::TRACK_CHUNK_KIND,
+        },
+        event::track::{
+            TRACK_EVENT_DATA_00_MIN_MIDI_RUNNING, TRACK_EVENT_DATA_7F_MAX_MIDI_RUNNING,
+            TRACK_EVENT_STATUS_80_MIN_MIDI, TRACK_EVENT_STATUS_EF_MAX_MIDI,
+            TRACK_EVENT_STATUS_F0_SOX, TRACK_EVENT_STATUS_F7_EOX, TRACK_EVENT_STATUS_FF_META,
+        },
+    },
+    options::{LenientMode, ParseOptions},
+    scanner::Scanner,
+};
+
+/// The meta-event type of `EndOfTrack`.
+const END_OF_TRACK_KIND: u8 = 0x2F;
+
+/// The offset of the `tracks_count` field of a header chunk in a file.
+const TRACKS_COUNT_OFFSET: usize = 10;
+
+/// The first deviation from the specification found by [`parse_strict`].
+#[derive(Debug, Display, Error)]
+#[display("{kind} at offset {offset}")]
+pub struct StrictError {
+    /// The index in the file of the first byte that does not conform.
+    pub offset: usize,
+
+    pub kind: StrictErrorKind,
+}
+
+#[derive(Debug, Display, Error)]
+pub enum StrictErrorKind {
+    /// The file does not start with a header chunk.
+    MissingHeader,
+
+    /// The header chunk does not declare a length of exactly 6 bytes.
+    InvalidHeaderLength(#[error(ignore)] u32),
+
+    /// A header chunk follows the first chunk.
+    UnexpectedHeader,
+
+    /// A chunk runs past the end of the file.
+    CouldNotReadChunk,
+
+    CouldNotReadVLQ,
+
+    /// A variable-length quantity starts with `0x80`, so it has a shorter
+    /// encoding.
+    RedundantVLQ,
+
+    /// An event runs past the end of its track chunk.
+    CouldNotReadEvent,
+
+    RunningStatusNotSet,
+
+    /// A System Common or System Real-Time status byte, which is not valid in
+    /// a track chunk.
+    #[debug("InvalidStatus({:X})", _0)]
+    InvalidStatus(#[error(ignore)] u8),
+
+    /// A track chunk does not end with an `EndOfTrack` meta-event. The offset
+    /// is that of the end of the track chunk.
+    MissingEndOfTrack,
+
+    /// The header chunk declares a number of track chunks other than the
+    /// number found in the file.
+    #[display("TracksCountMismatch({declared}, {found})")]
+    TracksCountMismatch {
+        declared: u16,
+        found: usize,
+    },
+
+    /// The chunk could not be decoded. The offset is that of the chunk.
+    ChunkFileToChunk(crate::core::chunk::TryFromError),
+}
+
+/// Parses `bytes` into a [`MIDI`], failing on the first deviation from the
+/// Standard MIDI File specification.
+///
+/// On top of what `MIDI::try_from` checks, the file must start with a header
+/// chunk of exactly 6 bytes, even though the specification asks readers to
+/// honor longer ones, and contain no other header chunk. Every track chunk
+/// must end with an `EndOfTrack` meta-event and contain no System Common or
+/// System Real-Time status byte, every variable-length quantity must use its
+/// shortest encoding, and the header's `tracks_count` must match the number of
+/// track chunks.
+pub fn parse_strict(bytes: Vec<u8>) -> Result<MIDI, StrictError> {
+    let options = ParseOptions {
+        invalid_status: LenientMode::Error,
+        ..ParseOptions::default()
+    };
+    let mut scanner = Scanner::new(&bytes);
+    let mut chunks = Vec::new();
+    let mut tracks_count: usize = 0;
+
+    if scanner.done() {
+        return Err(StrictError {
+            offset: 0,
+            kind: StrictErrorKind::MissingHeader,
+        });
+    }
+
+    while !scanner.done() {
+        let offset = scanner.cursor();
+        let error = |kind| StrictError { offset, kind };
+
+        let kind = scanner
+            .eat_bytes::<4>()
+            .ok_or(error(StrictErrorKind::CouldNotReadChunk))?;
+        let length = scanner
+            .eat_u32_be()
+            .ok_or(error(StrictErrorKind::CouldNotReadChunk))?;
+        let data = scanner
+            .eat_slice(length as usize)
+            .ok_or(error(StrictErrorKind::CouldNotReadChunk))?;
+
+        match kind {
+            HEADER_CHUNK_KIND if offset != 0 => {
+                return Err(error(StrictErrorKind::UnexpectedHeader));
+            }
+            HEADER_CHUNK_KIND if length != *HEADER_CHUNK_LENGTH => {
+                return Err(StrictError {
+                    offset: offset + 4,
+                    kind: StrictErrorKind::InvalidHeaderLength(length),
+                });
+            }
+            HEADER_CHUNK_KIND => {}
+            _ if offset == 0 => return Err(error(StrictErrorKind::MissingHeader)),
+            TRACK_CHUNK_KIND => {
+                check_track_events(data, offset + 8)?;
+                tracks_count += 1;
+            }
+            _ => {}
+        }
+
+        let chunk_file = ChunkFile {
+            kind,
+            length,
+            offset,
+            data,
+        };
+        let chunk = Chunk::try_from_with_options(&chunk_file, &options)
+            .map_err(|source| error(StrictErrorKind::ChunkFileToChunk(source)))?;
+        chunks.push(chunk);
+    }
+
+    let midi = MIDI(chunks);
+    if let Some(header_chunk) = midi.header()
+        && usize::from(header_chunk.tracks_count) != tracks_count
+    {
+        return Err(StrictError {
+            offset: TRACKS_COUNT_OFFSET,
+            kind: StrictErrorKind::TracksCountMismatch {
+                declared: header_chunk.tracks_count,
+                found: tracks_count,
+            },
+        });
+    }
+
+    Ok(midi)
+}
+
+/// Walks the events of a track chunk, whose `track_events` start at `offset`
+/// in the file, checking what `TrackEventsFile` leniently accepts.
+fn check_track_events(track_events: &[u8], offset: usize) -> Result<(), StrictError> {
+    let mut scanner = Scanner::new(track_events);
+    let mut running_status: Option<u8> = None;
+    let mut ends_with_end_of_track = false;
+
+    while !scanner.done() {
+        eat_variable_length_quantity(&mut scanner, offset)?;
+
+        let status_offset = offset + scanner.cursor();
+        let error = |kind| StrictError {
+            offset: status_offset,
+            kind,
+        };
+        let status = *scanner
+            .eat()
+            .ok_or(error(StrictErrorKind::CouldNotReadEvent))?;
+        ends_with_end_of_track = false;
+
+        let length = match status {
+            TRACK_EVENT_DATA_00_MIN_MIDI_RUNNING..=TRACK_EVENT_DATA_7F_MAX_MIDI_RUNNING => {
+                let running_status =
+                    running_status.ok_or(error(StrictErrorKind::RunningStatusNotSet))?;
+                // The status byte was a data byte under running status.
+                channel_message_data_len(running_status) - 1
+            }
+            TRACK_EVENT_STATUS_80_MIN_MIDI..=TRACK_EVENT_STATUS_EF_MAX_MIDI => {
+                running_status = Some(status);
+                channel_message_data_len(status)
+            }
+            TRACK_EVENT_STATUS_FF_META => {
+                running_status = None;
+                let kind = *scanner
+                    .eat()
+                    .ok_or(error(StrictErrorKind::CouldNotReadEvent))?;
+                ends_with_end_of_track = kind == END_OF_TRACK_KIND;
+                eat_variable_length_quantity(&mut scanner, offset)? as usize
+            }
+            TRACK_EVENT_STATUS_F0_SOX | TRACK_EVENT_STATUS_F7_EOX => {
+                running_status = None;
+                eat_variable_length_quantity(&mut scanner, offset)? as usize
+            }
+            _ => return Err(error(StrictErrorKind::InvalidStatus(status))),
+        };
+
+        scanner
+            .eat_slice(length)
+            .ok_or(error(StrictErrorKind::CouldNotReadEvent))?;
+    }
+
+    if !ends_with_end_of_track {
+        return Err(StrictError {
+            offset: offset + track_events.len(),
+            kind: StrictErrorKind::MissingEndOfTrack,
+        });
+    }
+    Ok(())
+}
+
+/// Consumes a variable-length quantity in its shortest encoding from the
+/// track chunk `scanner`, whose bytes start at `offset` in the file.
+fn eat_variable_length_quantity(scanner: &mut Scanner, offset: usize) -> Result<u32, StrictError> {
+    let offset = offset + scanner.cursor();
+    let kind = match scanner.peek() {
+        Some(0x80) => StrictErrorKind::RedundantVLQ,
+        _ => StrictErrorKind::CouldNotReadVLQ,
+    };
+    scanner
+        .eat_variable_length_quantity_strict()
+        .ok_or(StrictError { offset, kind })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+
+    const GOOD_TRACK: &[u8] = b"\0\x90\x3C\x40\x10\x3C\0\0\xFF\x2F\0";
+
+    /// A Format 1 file whose header has a length of `header_length`, declares
+    /// `tracks_count` tracks and is followed by `tracks`.
+    fn file(header_length: u32, tracks_count: u16, tracks: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = b"MThd".to_vec();
+        bytes.extend(header_length.to_be_bytes());
+        bytes.extend([0x00, 0x01]);
+        bytes.extend(tracks_count.to_be_bytes());
+        bytes.extend([0x00, 0x60]);
+        bytes.extend(vec![0; header_length.saturating_sub(6) as usize]);
+        for track in tracks {
+            bytes.extend(b"MTrk");
+            bytes.extend((track.len() as u32).to_be_bytes());
+            bytes.extend(*track);
+        }
+        bytes
+    }
+
+    /// The kind and offset of the error of `parse_strict` on `bytes`.
+    fn error(bytes: Vec<u8>) -> (StrictErrorKind, usize) {
+        let error = parse_strict(bytes).unwrap_err();
+        (error.kind, error.offset)
+    }
+
+    #[test]
+    fn conformant_file_parses() {
+        let midi = parse_strict(file(6, 2, &[GOOD_TRACK, GOOD_TRACK])).unwrap();
+
+        assert_eq!(midi.tracks().count(), 2);
+    }
+
+    #[test]
+    fn non_conformant_files_are_rejected_at_their_offset() {
+        assert!(matches!(
+            error(file(8, 1, &[GOOD_TRACK])),
+            (StrictErrorKind::InvalidHeaderLength(8), 4)
+        ));
+        assert!(matches!(
+            error(file(6, 1, &[b"\0\x90\x3C\x40\0\xF8\0\xFF\x2F\0"])),
+            (StrictErrorKind::InvalidStatus(0xF8), 27)
+        ));
+        assert!(matches!(
+            error(file(6, 1, &[b"\0\x90\x3C\x40"])),
+            (StrictErrorKind::MissingEndOfTrack, 26)
+        ));
+        assert!(matches!(
+            error(file(6, 3, &[GOOD_TRACK, GOOD_TRACK])),
+            (
+                StrictErrorKind::TracksCountMismatch {
+                    declared: 3,
+                    found: 2
+                },
+                10
+            )
+        ));
+        assert!(matches!(
+            error(file(6, 1, &[b"\x80\x10\x90\x3C\x40\0\xFF\x2F\0"])),
+            (StrictErrorKind::RedundantVLQ, 22)
+        ));
+        assert!(matches!(
+            error(file(6, 1, &[b"\0\x3C\x40\0\xFF\x2F\0"])),
+            (StrictErrorKind::RunningStatusNotSet, _)
+        ));
+        assert!(matches!(
+            error(Vec::new()),
+            (StrictErrorKind::MissingHeader, _)
+        ));
+    }
+}
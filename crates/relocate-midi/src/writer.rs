@@ -0,0 +1,75 @@
+//! A module providing helpers for writing MIDI data, the counterpart of the
+//! [`Scanner`](crate::scanner::Scanner).
+
+use alloc::vec::Vec;
+
+/// The largest value a variable-length quantity can hold in the 4 bytes
+/// allowed by the MIDI Specification and read back by the
+/// [`Scanner`](crate::scanner::Scanner).
+pub(crate) const VARIABLE_LENGTH_QUANTITY_MAX: u64 = 0x0FFF_FFFF;
+
+/// Append `value` to `bytes` as a variable-length quantity as defined in the
+/// MIDI Specification.
+///
+/// Returns `None`, leaving `bytes` unchanged, if `value` is larger than
+/// [`VARIABLE_LENGTH_QUANTITY_MAX`], rather than write more than 4 bytes.
+pub(crate) fn push_variable_length_quantity(bytes: &mut Vec<u8>, value: u64) -> Option<()> {
+    if value > VARIABLE_LENGTH_QUANTITY_MAX {
+        return None;
+    }
+
+    let count = variable_length_quantity_len(value);
+    for index in (0..count).rev() {
+        let continuation = if index == 0 { 0x00 } else { 0x80 };
+        bytes.push(((value >> (7 * index)) as u8 & 0x7F) | continuation);
+    }
+    Some(())
+}
+
+/// The number of bytes `value` takes as a variable-length quantity, counting
+/// as many 7-bit groups as it needs even past [`VARIABLE_LENGTH_QUANTITY_MAX`].
+pub(crate) fn variable_length_quantity_len(value: u64) -> usize {
+    let mut count = 1;
+    let mut rest = value >> 7;
+    while rest != 0 {
+        count += 1;
+        rest >>= 7;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn push_variable_length_quantity_of_max_takes_4_bytes() {
+        let mut bytes = vec![];
+
+        assert_eq!(
+            push_variable_length_quantity(&mut bytes, VARIABLE_LENGTH_QUANTITY_MAX),
+            Some(())
+        );
+        assert_eq!(bytes, [0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn push_variable_length_quantity_rejects_above_max() {
+        let mut bytes = vec![0x00];
+
+        assert_eq!(
+            push_variable_length_quantity(&mut bytes, u64::from(u32::MAX)),
+            None
+        );
+        assert_eq!(bytes, [0x00]);
+    }
+
+    #[test]
+    fn variable_length_quantity_len_of_group_boundaries() {
+        for (value, len) in [(0, 1), (0x7F, 1), (0x80, 2), (0x3FFF, 2), (0x4000, 3)] {
+            assert_eq!(variable_length_quantity_len(value), len);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use relocate_midi::core::{chunk::Chunk, midi::MIDI};
+use relocate_midi::prelude::*;
 use std::fs;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {